@@ -0,0 +1,23 @@
+//! Serves untrusted requests.  Every response must fit the UDP limit and be a valid message.
+#![no_main]
+use ddns::{process_datagram, DnsMessageRef, DnsRecord, ServerConfig, Zones};
+use fixed_buffer::FixedBuf;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let records = [
+        DnsRecord::new_a("aaa.example.com", "10.0.0.1").unwrap(),
+        DnsRecord::new_aaaa("aaa.example.com", "2606:2800:220:1:248:1893:25c8:1946").unwrap(),
+        DnsRecord::new_cname("bbb.example.com", "aaa.example.com").unwrap(),
+    ];
+    let zones = Zones::new(&records);
+    let mut buf: FixedBuf<{ ddns::MAX_UDP_PAYLOAD_SIZE }> = FixedBuf::new();
+    if buf.write_bytes(data).is_err() {
+        return;
+    }
+    if let Ok(response) = process_datagram(&ServerConfig::default(), &zones, &mut buf) {
+        assert!(response.len() <= ddns::MAX_UDP_PAYLOAD_SIZE);
+        let response = DnsMessageRef::new(response.readable()).unwrap();
+        assert!(response.header().is_response);
+    }
+});
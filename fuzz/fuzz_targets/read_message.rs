@@ -0,0 +1,22 @@
+//! Reads untrusted bytes with every message reader.  They must return errors, never panic.
+#![no_main]
+use ddns::{DnsMessage, DnsMessageRef};
+use fixed_buffer::FixedBuf;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut buf: FixedBuf<65535> = FixedBuf::new();
+    if buf.write_bytes(data).is_err() {
+        return;
+    }
+    let _ = DnsMessage::read(&mut buf.clone());
+    let _ = DnsMessage::read_strict(&mut buf.clone());
+    if let Ok((message, _warnings)) = DnsMessage::read_lenient(&mut buf.clone()) {
+        let _ = message.to_string();
+    }
+    if let Ok(message) = DnsMessageRef::new(data) {
+        let _ = message.edns();
+        let _ = message.response_code();
+        let _ = message.to_message();
+    }
+});
@@ -0,0 +1,30 @@
+//! Reads untrusted bytes, writes the message, and reads it again.  The second read must return
+//! the same message, and writing it again must give the same bytes.
+#![no_main]
+use ddns::DnsMessage;
+use fixed_buffer::FixedBuf;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut buf: FixedBuf<65535> = FixedBuf::new();
+    if buf.write_bytes(data).is_err() {
+        return;
+    }
+    let Ok(message) = DnsMessage::read_strict(&mut buf) else {
+        return;
+    };
+    let mut out: FixedBuf<65535> = FixedBuf::new();
+    if message.write(&mut out).is_err() {
+        return;
+    }
+    let written = out.readable().to_vec();
+    let expected = DnsMessage {
+        header: message.counted_header().unwrap(),
+        ..message
+    };
+    let message = DnsMessage::read_strict(&mut out).unwrap();
+    assert_eq!(expected, message);
+    let mut out: FixedBuf<65535> = FixedBuf::new();
+    message.write(&mut out).unwrap();
+    assert_eq!(written, out.readable());
+});
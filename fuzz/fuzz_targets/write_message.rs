@@ -0,0 +1,17 @@
+//! Writes generated messages and reads them back.
+#![no_main]
+use ddns::DnsMessage;
+use fixed_buffer::FixedBuf;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|message: DnsMessage| {
+    let mut out: FixedBuf<65535> = FixedBuf::new();
+    if message.write(&mut out).is_err() {
+        return;
+    }
+    let expected = DnsMessage {
+        header: message.counted_header().unwrap(),
+        ..message
+    };
+    assert_eq!(expected, DnsMessage::read_strict(&mut out).unwrap());
+});
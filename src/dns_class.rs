@@ -1,5 +1,8 @@
-use crate::{read_u16_be, write_u16_be, DnsError};
-use fixed_buffer::FixedBuf;
+use crate::{read_u16_be, write_u16_be, DnsError, FixedBuf};
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
 
 /// > `CLASS` fields appear in resource records.  The following `CLASS` mnemonics and values are
 /// > defined:
@@ -17,7 +20,7 @@ use fixed_buffer::FixedBuf;
 /// > - `*` 255 any class
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.4>
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum DnsClass {
     Internet,
     Any,
@@ -54,3 +57,49 @@ impl DnsClass {
         write_u16_be(out, self.num())
     }
 }
+impl Display for DnsClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            DnsClass::Internet => write!(f, "IN"),
+            DnsClass::Any => write!(f, "ANY"),
+            DnsClass::Unknown(n) => write!(f, "CLASS{n}"),
+        }
+    }
+}
+impl FromStr for DnsClass {
+    type Err = String;
+
+    /// Parses a class mnemonic, or `CLASS` and the number.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc3597#section-5>
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let upper = value.to_ascii_uppercase();
+        match upper.as_str() {
+            "IN" => Ok(DnsClass::Internet),
+            "ANY" => Ok(DnsClass::Any),
+            other => other
+                .strip_prefix("CLASS")
+                .and_then(|n| n.parse().ok())
+                .map(DnsClass::new)
+                .ok_or_else(|| format!("not a DNS class: {value:?}")),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsClass {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    for class in [DnsClass::Internet, DnsClass::Any, DnsClass::Unknown(3)] {
+        assert_eq!(Ok(class), class.to_string().parse());
+    }
+    assert_eq!(Ok(DnsClass::Internet), "in".parse());
+    assert_eq!(Ok(DnsClass::Internet), "CLASS1".parse());
+    "CH".parse::<DnsClass>().unwrap_err();
+}
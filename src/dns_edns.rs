@@ -0,0 +1,320 @@
+use crate::{
+    hex_decode, hex_encode, read_u16_be, read_u32_be, write_bytes, write_u16_be, write_u32_be,
+    DnsEdnsOption, DnsError, DnsName, DnsType, FixedBuf,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+/// The EDNS(0) OPT pseudo-record.
+///
+/// > An OPT RR has a fixed part and a variable set of options expressed as {attribute, value}
+/// > pairs.  The fixed part holds some DNS metadata, and also a small collection of basic
+/// > extension elements that we expect to be so popular that it would be a waste of wire space to
+/// > encode them as {attribute, value} pairs.
+/// >
+/// > The fixed part of an OPT RR is structured as follows:
+/// >
+/// > ```text
+/// >        +------------+--------------+------------------------------+
+/// >        | Field Name | Field Type   | Description                  |
+/// >        +------------+--------------+------------------------------+
+/// >        | NAME       | domain name  | MUST be 0 (root domain)      |
+/// >        | TYPE       | u_int16_t    | OPT (41)                     |
+/// >        | CLASS      | u_int16_t    | requestor's UDP payload size |
+/// >        | TTL        | u_int32_t    | extended RCODE and flags     |
+/// >        | RDLEN      | u_int16_t    | length of all RDATA          |
+/// >        | RDATA      | octet stream | {attribute,value} pairs      |
+/// >        +------------+--------------+------------------------------+
+/// > ```
+///
+/// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.2>
+///
+/// > The extended RCODE and flags, which OPT stores in the RR Time to Live (TTL) field, are
+/// > structured as follows:
+/// >
+/// > ```text
+/// >                   +0 (MSB)                            +1 (LSB)
+/// >        +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// >     0: |         EXTENDED-RCODE        |            VERSION            |
+/// >        +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// >     2: | DO|                           Z                               |
+/// >        +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// > ```
+///
+/// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3>
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DnsEdns {
+    /// > `CLASS` This field contains the requestor's UDP payload size.  Values lower than 512 MUST
+    /// > be treated as equal to 512.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.2>
+    pub udp_payload_size: u16,
+    /// > `EXTENDED-RCODE` Forms the upper 8 bits of extended 12-bit RCODE (together with the
+    /// > 4 bits defined in [RFC1035].  Note that EXTENDED-RCODE value 0 indicates that an
+    /// > unextended RCODE is in use (values 0 through 15).
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3>
+    pub extended_response_code: u8,
+    /// > `VERSION` Indicates the implementation level of the setter.  Full conformance with this
+    /// > specification is indicated by version '0'.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3>
+    pub version: u8,
+    /// > `DO` DNSSEC OK bit as defined by [RFC3225].
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3>
+    pub dnssec_ok: bool,
+    pub options: Vec<DnsEdnsOption>,
+}
+impl DnsEdns {
+    /// Makes a version 0 OPT record with no options.
+    #[must_use]
+    pub fn new(udp_payload_size: u16) -> Self {
+        Self {
+            udp_payload_size,
+            extended_response_code: 0,
+            version: 0,
+            dnssec_ok: false,
+            options: Vec::new(),
+        }
+    }
+
+    /// The advertised UDP payload size, treating values lower than 512 as 512.
+    #[must_use]
+    pub fn max_payload_size(&self) -> u16 {
+        self.udp_payload_size.max(512)
+    }
+
+    /// # Errors
+    /// Returns an error when `buf` does not contain a valid OPT record.
+    pub fn read<const N: usize>(buf: &mut FixedBuf<N>) -> Result<Self, DnsError> {
+        let name = DnsName::read(buf)?;
+        if !name.is_root() {
+            return Err(DnsError::InvalidOptRecord);
+        }
+        if DnsType::read(buf)? != DnsType::OPT {
+            return Err(DnsError::InvalidOptRecord);
+        }
+        let udp_payload_size = read_u16_be(buf)?;
+        let ttl = read_u32_be(buf)?;
+        let rdata_len = usize::from(read_u16_be(buf)?);
+        let rdata = buf.try_read_bytes(rdata_len).ok_or(DnsError::Truncated)?;
+        Self::from_fields(udp_payload_size, ttl, rdata)
+    }
+
+    /// Makes an OPT record from the CLASS, TTL, and RDATA fields of a resource record.
+    ///
+    /// # Errors
+    /// Returns an error when `rdata` does not contain a whole number of options.
+    pub fn from_fields(udp_payload_size: u16, ttl: u32, rdata: &[u8]) -> Result<Self, DnsError> {
+        let ttl = ttl.to_be_bytes();
+        let mut options = Vec::new();
+        let mut offset = 0;
+        while offset < rdata.len() {
+            let (option, next) =
+                DnsEdnsOption::read_at(rdata, offset).map_err(|_| DnsError::InvalidRdata)?;
+            options.push(option);
+            offset = next;
+        }
+        Ok(Self {
+            udp_payload_size,
+            extended_response_code: ttl[0],
+            version: ttl[1],
+            dnssec_ok: (ttl[2] >> 7) == 1,
+            options,
+        })
+    }
+
+    /// Returns the TTL field, which holds the extended RCODE, version, and flags.
+    pub(crate) fn ttl(&self) -> u32 {
+        u32::from_be_bytes([
+            self.extended_response_code,
+            self.version,
+            u8::from(self.dnssec_ok) << 7,
+            0,
+        ])
+    }
+
+    /// # Errors
+    /// Returns an error when `buf` fills up.
+    pub fn write<const N: usize>(&self, out: &mut FixedBuf<N>) -> Result<(), DnsError> {
+        let rdata_len: usize = self
+            .options
+            .iter()
+            .map(|option| 4 + option.data.len())
+            .sum();
+        let rdata_len = u16::try_from(rdata_len).map_err(|_| DnsError::InvalidRdata)?;
+        write_bytes(out, &[0])?; // Root name.
+        DnsType::OPT.write(out)?;
+        write_u16_be(out, self.udp_payload_size)?;
+        write_u32_be(out, self.ttl())?;
+        write_u16_be(out, rdata_len)?;
+        for option in &self.options {
+            option.write(out)?;
+        }
+        Ok(())
+    }
+}
+/// Writes the OPT record like dig's `OPT PSEUDOSECTION`, with one line per option:
+///
+/// ```text
+/// ; EDNS: version: 0, flags: do; udp: 1232
+/// ; OPT=10: 0102030405
+/// ```
+///
+/// A non-zero extended RCODE is written as `; ext-rcode: N` after the UDP payload size.
+impl Display for DnsEdns {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        let flags = if self.dnssec_ok { " do" } else { "" };
+        write!(
+            f,
+            "; EDNS: version: {}, flags:{flags}; udp: {}",
+            self.version, self.udp_payload_size
+        )?;
+        if self.extended_response_code != 0 {
+            write!(f, "; ext-rcode: {}", self.extended_response_code)?;
+        }
+        for option in &self.options {
+            write!(f, "\n; OPT={}: {}", option.code, hex_encode(&option.data))?;
+        }
+        Ok(())
+    }
+}
+/// Parses the lines written by [`Display`].
+impl FromStr for DnsEdns {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let err = || format!("not an EDNS pseudo-section: {value:?}");
+        let mut lines = value.lines().map(str::trim).filter(|line| !line.is_empty());
+        let fields = lines
+            .next()
+            .and_then(|line| line.strip_prefix("; EDNS:"))
+            .ok_or_else(err)?;
+        let mut edns = Self::new(0);
+        for field in fields.split([',', ';']) {
+            match field.trim().split_once(':').ok_or_else(err)? {
+                ("version", version) => edns.version = version.trim().parse().map_err(|_| err())?,
+                ("flags", flags) => {
+                    for flag in flags.split_ascii_whitespace() {
+                        match flag {
+                            "do" => edns.dnssec_ok = true,
+                            _ => return Err(format!("unknown EDNS flag {flag:?}: {value:?}")),
+                        }
+                    }
+                }
+                ("udp", size) => edns.udp_payload_size = size.trim().parse().map_err(|_| err())?,
+                ("ext-rcode", rcode) => {
+                    edns.extended_response_code = rcode.trim().parse().map_err(|_| err())?;
+                }
+                _ => return Err(err()),
+            }
+        }
+        for line in lines {
+            let (code, data) = line
+                .strip_prefix("; OPT=")
+                .and_then(|option| option.split_once(':'))
+                .ok_or_else(err)?;
+            edns.options.push(DnsEdnsOption {
+                code: code.parse().map_err(|_| err())?,
+                data: hex_decode(data.trim()).ok_or_else(err)?,
+            });
+        }
+        Ok(edns)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_write() {
+    let bytes = [
+        0, // root
+        0, 41, // OPT
+        0x10, 0x00, // udp_payload_size=4096
+        1, 0, 0x80, 0, // extended_response_code=1 version=0 DO=1
+        0, 9, // rdlen=9
+        0, 10, 0, 5, 1, 2, 3, 4, 5, // COOKIE
+    ];
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&bytes).unwrap();
+    let edns = DnsEdns::read(&mut buf).unwrap();
+    assert_eq!(
+        DnsEdns {
+            udp_payload_size: 4096,
+            extended_response_code: 1,
+            version: 0,
+            dnssec_ok: true,
+            options: vec![DnsEdnsOption {
+                code: 10,
+                data: vec![1, 2, 3, 4, 5]
+            }],
+        },
+        edns
+    );
+    assert!(buf.is_empty());
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    edns.write(&mut out).unwrap();
+    assert_eq!(bytes, out.readable());
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_invalid() {
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[1, b'a', 0, 0, 41, 2, 0, 0, 0, 0, 0, 0, 0])
+        .unwrap();
+    assert_eq!(Err(DnsError::InvalidOptRecord), DnsEdns::read(&mut buf));
+    // Option overruns RDATA.
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[0, 0, 41, 2, 0, 0, 0, 0, 0, 0, 4, 0, 10, 0, 1, 7, 0])
+        .unwrap();
+    assert_eq!(Err(DnsError::InvalidRdata), DnsEdns::read(&mut buf));
+}
+
+#[cfg(test)]
+#[test]
+fn test_max_payload_size() {
+    assert_eq!(512, DnsEdns::new(0).max_payload_size());
+    assert_eq!(512, DnsEdns::new(511).max_payload_size());
+    assert_eq!(1232, DnsEdns::new(1232).max_payload_size());
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    let edns = DnsEdns::new(1232);
+    assert_eq!("; EDNS: version: 0, flags:; udp: 1232", edns.to_string());
+    assert_eq!(Ok(edns), "; EDNS: version: 0, flags:; udp: 1232".parse());
+    let edns = DnsEdns {
+        udp_payload_size: 4096,
+        extended_response_code: 1,
+        version: 0,
+        dnssec_ok: true,
+        options: vec![
+            DnsEdnsOption {
+                code: 10,
+                data: vec![1, 2, 3, 4, 5],
+            },
+            DnsEdnsOption {
+                code: 12,
+                data: Vec::new(),
+            },
+        ],
+    };
+    assert_eq!(
+        "; EDNS: version: 0, flags: do; udp: 4096; ext-rcode: 1\n; OPT=10: 0102030405\n; OPT=12: ",
+        edns.to_string()
+    );
+    assert_eq!(Ok(edns.clone()), edns.to_string().parse());
+    "; EDNS: version: 0, flags: xx; udp: 4096"
+        .parse::<DnsEdns>()
+        .unwrap_err();
+    "; EDNS: version: 0, flags:; udp: 4096\n; OPT=10: 012"
+        .parse::<DnsEdns>()
+        .unwrap_err();
+}
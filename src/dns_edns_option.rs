@@ -0,0 +1,66 @@
+use crate::{write_bytes, write_u16_be, DnsError, FixedBuf};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// > The variable part of an OPT RR may contain zero or more options in the RDATA.  Each option
+/// > MUST be treated as a bit field.  Each option is encoded as:
+/// >
+/// > ```text
+/// >                +0 (MSB)                            +1 (LSB)
+/// >     +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// >  0: |                          OPTION-CODE                          |
+/// >     +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// >  2: |                         OPTION-LENGTH                         |
+/// >     +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// >  4: |                                                               |
+/// >     /                          OPTION-DATA                          /
+/// >     /                                                               /
+/// >     +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// > ```
+/// >
+/// > - `OPTION-CODE` Assigned by the Expert Review process as defined by the DNSEXT working group
+/// >   and the IESG.
+/// > - `OPTION-LENGTH` Size (in octets) of OPTION-DATA.
+/// > - `OPTION-DATA` Varies per OPTION-CODE.  MUST be treated as a bit field.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.2>
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DnsEdnsOption {
+    pub code: u16,
+    pub data: Vec<u8>,
+}
+impl DnsEdnsOption {
+    /// # Errors
+    /// Returns an error when `buf` does not contain a complete option.
+    pub fn read<const N: usize>(buf: &mut FixedBuf<N>) -> Result<Self, DnsError> {
+        let (option, len) = Self::read_at(buf.readable(), 0)?;
+        buf.read_bytes(len);
+        Ok(option)
+    }
+
+    /// Reads the option at `offset` in `bytes`.  Returns the option and the offset of the byte
+    /// after it.
+    ///
+    /// # Errors
+    /// Returns an error when `bytes` does not contain a complete option at `offset`.
+    pub fn read_at(bytes: &[u8], offset: usize) -> Result<(Self, usize), DnsError> {
+        let fixed = bytes.get(offset..offset + 4).ok_or(DnsError::Truncated)?;
+        let code = u16::from_be_bytes([fixed[0], fixed[1]]);
+        let len = usize::from(u16::from_be_bytes([fixed[2], fixed[3]]));
+        let data = bytes
+            .get(offset + 4..offset + 4 + len)
+            .ok_or(DnsError::Truncated)?
+            .to_vec();
+        Ok((Self { code, data }, offset + 4 + len))
+    }
+
+    /// # Errors
+    /// Returns an error when `buf` fills up or the data is longer than 65,535 bytes.
+    pub fn write<const N: usize>(&self, out: &mut FixedBuf<N>) -> Result<(), DnsError> {
+        let len = u16::try_from(self.data.len()).map_err(|_| DnsError::InvalidRdata)?;
+        write_u16_be(out, self.code)?;
+        write_u16_be(out, len)?;
+        write_bytes(out, &self.data)
+    }
+}
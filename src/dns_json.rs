@@ -0,0 +1,681 @@
+//! The JSON form of messages from RFC 8427.  Enable the `serde` feature to use it.
+//!
+//! <https://datatracker.ietf.org/doc/html/rfc8427>
+//!
+//! Every member is optional when reading.  Flags are written as `0` or `1`, like the RFC's
+//! examples, and read as either numbers or JSON booleans.  The OPT record appears in
+//! `additionalRRs` with `TYPE` 41, like it does on the wire.
+use crate::{
+    hex_decode, hex_encode, DnsClass, DnsEdns, DnsError, DnsMessage, DnsMessageHeader, DnsName,
+    DnsOpCode, DnsQuestion, DnsRecord, DnsRecordData, DnsResponseCode, DnsType,
+};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Reads and writes a header flag as `0` or `1`.
+mod flag {
+    use alloc::format;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(u8::from(*value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Flag {
+            Bool(bool),
+            Number(u8),
+        }
+        match Flag::deserialize(deserializer)? {
+            Flag::Bool(value) => Ok(value),
+            Flag::Number(0) => Ok(false),
+            Flag::Number(1) => Ok(true),
+            Flag::Number(n) => Err(serde::de::Error::custom(format!(
+                "flag must be 0 or 1, not {n}"
+            ))),
+        }
+    }
+}
+
+fn name_to_json(name: &DnsName) -> String {
+    if name.is_root() {
+        ".".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Returns the type from the `TYPE` member, or else the `TYPEname` member.
+fn type_from_json(num: Option<u16>, name: Option<&str>) -> Result<DnsType, String> {
+    match (num, name) {
+        (Some(num), _) => Ok(DnsType::new(num)),
+        (None, Some(name)) => name.parse(),
+        (None, None) => Err("missing TYPE".to_string()),
+    }
+}
+
+/// Returns the class from the `CLASS` member, or else the `CLASSname` member.  Defaults to `IN`.
+fn class_from_json(num: Option<u16>, name: Option<&str>) -> Result<DnsClass, String> {
+    match (num, name) {
+        (Some(num), _) => Ok(DnsClass::new(num)),
+        (None, Some(name)) => name.parse(),
+        (None, None) => Ok(DnsClass::Internet),
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct HeaderJson {
+    #[serde(rename = "ID", default)]
+    id: u16,
+    #[serde(rename = "QR", default, with = "flag")]
+    qr: bool,
+    #[serde(rename = "Opcode", default)]
+    op_code: u8,
+    #[serde(rename = "AA", default, with = "flag")]
+    aa: bool,
+    #[serde(rename = "TC", default, with = "flag")]
+    tc: bool,
+    #[serde(rename = "RD", default, with = "flag")]
+    rd: bool,
+    #[serde(rename = "RA", default, with = "flag")]
+    ra: bool,
+    #[serde(rename = "AD", default, with = "flag")]
+    ad: bool,
+    #[serde(rename = "CD", default, with = "flag")]
+    cd: bool,
+    #[serde(rename = "RCODE", default)]
+    response_code: u8,
+    #[serde(rename = "QDCOUNT", default, skip_serializing_if = "Option::is_none")]
+    question_count: Option<u16>,
+    #[serde(rename = "ANCOUNT", default, skip_serializing_if = "Option::is_none")]
+    answer_count: Option<u16>,
+    #[serde(rename = "NSCOUNT", default, skip_serializing_if = "Option::is_none")]
+    name_server_count: Option<u16>,
+    #[serde(rename = "ARCOUNT", default, skip_serializing_if = "Option::is_none")]
+    additional_count: Option<u16>,
+}
+impl HeaderJson {
+    fn new(header: &DnsMessageHeader) -> Self {
+        Self {
+            id: header.id,
+            qr: header.is_response,
+            op_code: header.op_code.num(),
+            aa: header.authoritative_answer,
+            tc: header.truncated,
+            rd: header.recursion_desired,
+            ra: header.recursion_available,
+            ad: header.authentic_data,
+            cd: header.checking_disabled,
+            response_code: header.response_code.header_bits(),
+            question_count: Some(header.question_count),
+            answer_count: Some(header.answer_count),
+            name_server_count: Some(header.name_server_count),
+            additional_count: Some(header.additional_count),
+        }
+    }
+
+    /// Makes a header.  Missing counts are zero.
+    fn into_header(self) -> Result<DnsMessageHeader, String> {
+        if self.op_code > 15 {
+            return Err(format!("Opcode must be 0 to 15, not {}", self.op_code));
+        }
+        if self.response_code > 15 {
+            return Err(format!("RCODE must be 0 to 15, not {}", self.response_code));
+        }
+        Ok(DnsMessageHeader {
+            id: self.id,
+            is_response: self.qr,
+            op_code: DnsOpCode::new(self.op_code),
+            authoritative_answer: self.aa,
+            truncated: self.tc,
+            recursion_desired: self.rd,
+            recursion_available: self.ra,
+            authentic_data: self.ad,
+            checking_disabled: self.cd,
+            response_code: DnsResponseCode::new(u16::from(self.response_code)),
+            question_count: self.question_count.unwrap_or(0),
+            answer_count: self.answer_count.unwrap_or(0),
+            name_server_count: self.name_server_count.unwrap_or(0),
+            additional_count: self.additional_count.unwrap_or(0),
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct QuestionJson {
+    #[serde(rename = "NAME")]
+    name: String,
+    #[serde(rename = "TYPE", default, skip_serializing_if = "Option::is_none")]
+    typ: Option<u16>,
+    #[serde(rename = "TYPEname", default, skip_serializing_if = "Option::is_none")]
+    type_name: Option<String>,
+    #[serde(rename = "CLASS", default, skip_serializing_if = "Option::is_none")]
+    class: Option<u16>,
+    #[serde(rename = "CLASSname", default, skip_serializing_if = "Option::is_none")]
+    class_name: Option<String>,
+}
+impl QuestionJson {
+    fn new(question: &DnsQuestion) -> Self {
+        Self {
+            name: name_to_json(&question.name),
+            typ: Some(question.typ.num()),
+            type_name: Some(question.typ.to_string()),
+            class: Some(question.class.num()),
+            class_name: Some(question.class.to_string()),
+        }
+    }
+
+    fn into_question(self) -> Result<DnsQuestion, String> {
+        Ok(DnsQuestion {
+            name: self.name.parse()?,
+            typ: type_from_json(self.typ, self.type_name.as_deref())?,
+            class: class_from_json(self.class, self.class_name.as_deref())?,
+        })
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct RecordJson {
+    #[serde(rename = "NAME")]
+    name: String,
+    #[serde(rename = "TYPE", default, skip_serializing_if = "Option::is_none")]
+    typ: Option<u16>,
+    #[serde(rename = "TYPEname", default, skip_serializing_if = "Option::is_none")]
+    type_name: Option<String>,
+    #[serde(rename = "CLASS", default, skip_serializing_if = "Option::is_none")]
+    class: Option<u16>,
+    #[serde(rename = "CLASSname", default, skip_serializing_if = "Option::is_none")]
+    class_name: Option<String>,
+    #[serde(rename = "TTL", default, skip_serializing_if = "Option::is_none")]
+    ttl: Option<u32>,
+    #[serde(rename = "RDLENGTH", default, skip_serializing_if = "Option::is_none")]
+    rdata_len: Option<u16>,
+    #[serde(rename = "RDATAHEX", default, skip_serializing_if = "Option::is_none")]
+    rdata_hex: Option<String>,
+    #[serde(rename = "rdataA", default, skip_serializing_if = "Option::is_none")]
+    rdata_a: Option<String>,
+    #[serde(rename = "rdataAAAA", default, skip_serializing_if = "Option::is_none")]
+    rdata_aaaa: Option<String>,
+    #[serde(
+        rename = "rdataCNAME",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    rdata_cname: Option<String>,
+    #[serde(rename = "rdataMX", default, skip_serializing_if = "Option::is_none")]
+    rdata_mx: Option<String>,
+    #[serde(rename = "rdataNS", default, skip_serializing_if = "Option::is_none")]
+    rdata_ns: Option<String>,
+    #[serde(rename = "rdataPTR", default, skip_serializing_if = "Option::is_none")]
+    rdata_ptr: Option<String>,
+    #[serde(rename = "rdataSOA", default, skip_serializing_if = "Option::is_none")]
+    rdata_soa: Option<String>,
+    #[serde(rename = "rdataSRV", default, skip_serializing_if = "Option::is_none")]
+    rdata_srv: Option<String>,
+    #[serde(rename = "rdataTXT", default, skip_serializing_if = "Option::is_none")]
+    rdata_txt: Option<String>,
+}
+impl RecordJson {
+    fn new(record: &DnsRecord) -> Result<Self, DnsError> {
+        let rdata = record.data().to_bytes()?;
+        let text = || Some(record.data().to_string());
+        let data = record.data();
+        Ok(Self {
+            name: name_to_json(record.name()),
+            typ: Some(record.typ().num()),
+            type_name: Some(record.typ().to_string()),
+            class: Some(record.class().num()),
+            class_name: Some(record.class().to_string()),
+            ttl: Some(record.ttl_seconds()),
+            rdata_len: u16::try_from(rdata.len()).ok(),
+            rdata_hex: Some(hex_encode(&rdata)),
+            rdata_a: matches!(data, DnsRecordData::A(_)).then(text).flatten(),
+            rdata_aaaa: matches!(data, DnsRecordData::AAAA(_)).then(text).flatten(),
+            rdata_cname: matches!(data, DnsRecordData::CNAME(_)).then(text).flatten(),
+            rdata_mx: matches!(data, DnsRecordData::MX { .. })
+                .then(text)
+                .flatten(),
+            rdata_ns: matches!(data, DnsRecordData::NS(_)).then(text).flatten(),
+            rdata_ptr: matches!(data, DnsRecordData::PTR(_)).then(text).flatten(),
+            rdata_soa: matches!(data, DnsRecordData::SOA { .. })
+                .then(text)
+                .flatten(),
+            rdata_srv: matches!(data, DnsRecordData::SRV { .. })
+                .then(text)
+                .flatten(),
+            rdata_txt: matches!(data, DnsRecordData::TXT(_)).then(text).flatten(),
+        })
+    }
+
+    fn from_edns(edns: &DnsEdns) -> Self {
+        let mut rdata = Vec::new();
+        for option in &edns.options {
+            rdata.extend(option.code.to_be_bytes());
+            rdata.extend(u16::try_from(option.data.len()).unwrap_or(0).to_be_bytes());
+            rdata.extend(&option.data);
+        }
+        Self {
+            name: ".".to_string(),
+            typ: Some(DnsType::OPT.num()),
+            type_name: Some(DnsType::OPT.to_string()),
+            class: Some(edns.udp_payload_size),
+            ttl: Some(edns.ttl()),
+            rdata_len: u16::try_from(rdata.len()).ok(),
+            rdata_hex: Some(hex_encode(&rdata)),
+            ..Self::default()
+        }
+    }
+
+    fn typ(&self) -> Result<DnsType, String> {
+        type_from_json(self.typ, self.type_name.as_deref())
+    }
+
+    /// Returns the bytes of the `RDATAHEX` member, checking them against `RDLENGTH`.
+    fn rdata(&self) -> Result<Option<Vec<u8>>, String> {
+        let Some(hex) = &self.rdata_hex else {
+            return Ok(None);
+        };
+        let rdata = hex_decode(hex).ok_or_else(|| format!("invalid RDATAHEX: {hex:?}"))?;
+        match self.rdata_len {
+            Some(len) if usize::from(len) != rdata.len() => Err(format!(
+                "RDLENGTH {len} does not match {} bytes of RDATAHEX",
+                rdata.len()
+            )),
+            _ => Ok(Some(rdata)),
+        }
+    }
+
+    fn into_edns(self) -> Result<DnsEdns, String> {
+        let udp_payload_size = self
+            .class
+            .ok_or_else(|| "OPT record has no CLASS".to_string())?;
+        let rdata = self.rdata()?.unwrap_or_default();
+        DnsEdns::from_fields(udp_payload_size, self.ttl.unwrap_or(0), &rdata)
+            .map_err(|e| format!("invalid OPT record: {e:?}"))
+    }
+
+    /// Makes a record from `RDATAHEX`, or else from the `rdata` member for the type.
+    fn into_record(self) -> Result<DnsRecord, String> {
+        let typ = self.typ()?;
+        let data = if let Some(rdata) = self.rdata()? {
+            DnsRecordData::read_at(&typ, &rdata, 0, rdata.len())
+                .map_err(|e| format!("invalid RDATAHEX for type {typ}: {e:?}"))?
+        } else {
+            let text = match typ {
+                DnsType::A => self.rdata_a.as_deref(),
+                DnsType::AAAA => self.rdata_aaaa.as_deref(),
+                DnsType::CNAME => self.rdata_cname.as_deref(),
+                DnsType::MX => self.rdata_mx.as_deref(),
+                DnsType::NS => self.rdata_ns.as_deref(),
+                DnsType::PTR => self.rdata_ptr.as_deref(),
+                DnsType::SOA => self.rdata_soa.as_deref(),
+                DnsType::SRV => self.rdata_srv.as_deref(),
+                DnsType::TXT => self.rdata_txt.as_deref(),
+                _ => None,
+            }
+            .ok_or_else(|| format!("{typ} record has no RDATAHEX or rdata{typ}"))?;
+            DnsRecordData::parse(&typ, text)?
+        };
+        Ok(DnsRecord::new(
+            self.name.parse()?,
+            class_from_json(self.class, self.class_name.as_deref())?,
+            self.ttl.unwrap_or(DnsRecord::DEFAULT_TTL_SECONDS),
+            data,
+        ))
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct MessageJson {
+    #[serde(flatten)]
+    header: HeaderJson,
+    #[serde(rename = "QNAME", default, skip_serializing_if = "Option::is_none")]
+    question_name: Option<String>,
+    #[serde(rename = "QTYPE", default, skip_serializing_if = "Option::is_none")]
+    question_type: Option<u16>,
+    #[serde(rename = "QTYPEname", default, skip_serializing_if = "Option::is_none")]
+    question_type_name: Option<String>,
+    #[serde(rename = "QCLASS", default, skip_serializing_if = "Option::is_none")]
+    question_class: Option<u16>,
+    #[serde(
+        rename = "QCLASSname",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    question_class_name: Option<String>,
+    #[serde(rename = "questionRRs", default)]
+    questions: Vec<QuestionJson>,
+    #[serde(rename = "answerRRs", default)]
+    answers: Vec<RecordJson>,
+    #[serde(rename = "authorityRRs", default)]
+    name_servers: Vec<RecordJson>,
+    #[serde(rename = "additionalRRs", default)]
+    additional: Vec<RecordJson>,
+}
+impl MessageJson {
+    fn new(message: &DnsMessage) -> Result<Self, DnsError> {
+        let first = message.questions.first();
+        let records = |records: &[DnsRecord]| {
+            records
+                .iter()
+                .map(RecordJson::new)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let mut additional = records(&message.additional)?;
+        if let Some(edns) = &message.edns {
+            additional.push(RecordJson::from_edns(edns));
+        }
+        Ok(Self {
+            // Like `DnsMessage::write`, this writes the counts of the sections.
+            header: HeaderJson::new(
+                &message
+                    .counted_header()
+                    .unwrap_or_else(|_| message.header.clone()),
+            ),
+            question_name: first.map(|q| name_to_json(&q.name)),
+            question_type: first.map(|q| q.typ.num()),
+            question_type_name: first.map(|q| q.typ.to_string()),
+            question_class: first.map(|q| q.class.num()),
+            question_class_name: first.map(|q| q.class.to_string()),
+            questions: message.questions.iter().map(QuestionJson::new).collect(),
+            answers: records(&message.answers)?,
+            name_servers: records(&message.name_servers)?,
+            additional,
+        })
+    }
+
+    /// Makes a message.  When `questionRRs` is missing, the question comes from `QNAME`,
+    /// `QTYPE`, and `QCLASS`.  Missing counts come from the sections.
+    fn into_message(self) -> Result<DnsMessage, String> {
+        let mut questions = self
+            .questions
+            .into_iter()
+            .map(QuestionJson::into_question)
+            .collect::<Result<Vec<_>, _>>()?;
+        if questions.is_empty() {
+            if let Some(name) = self.question_name {
+                questions.push(DnsQuestion {
+                    name: name.parse()?,
+                    typ: type_from_json(self.question_type, self.question_type_name.as_deref())?,
+                    class: class_from_json(
+                        self.question_class,
+                        self.question_class_name.as_deref(),
+                    )?,
+                });
+            }
+        }
+        let records = |records: Vec<RecordJson>| {
+            records
+                .into_iter()
+                .map(RecordJson::into_record)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let answers = records(self.answers)?;
+        let name_servers = records(self.name_servers)?;
+        let mut additional = Vec::new();
+        let mut edns = None;
+        for record in self.additional {
+            if record.typ()? == DnsType::OPT {
+                if edns.is_some() {
+                    return Err("more than one OPT record".to_string());
+                }
+                edns = Some(record.into_edns()?);
+            } else {
+                additional.push(record.into_record()?);
+            }
+        }
+        let count = |len: usize| u16::try_from(len).map_err(|e| e.to_string());
+        let derived = [
+            count(questions.len())?,
+            count(answers.len())?,
+            count(name_servers.len())?,
+            count(additional.len() + usize::from(edns.is_some()))?,
+        ];
+        let mut json_header = self.header;
+        json_header.question_count = json_header.question_count.or(Some(derived[0]));
+        json_header.answer_count = json_header.answer_count.or(Some(derived[1]));
+        json_header.name_server_count = json_header.name_server_count.or(Some(derived[2]));
+        json_header.additional_count = json_header.additional_count.or(Some(derived[3]));
+        Ok(DnsMessage {
+            header: json_header.into_header()?,
+            questions,
+            answers,
+            name_servers,
+            additional,
+            edns,
+        })
+    }
+}
+
+impl Serialize for DnsMessageHeader {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HeaderJson::new(self).serialize(serializer)
+    }
+}
+impl<'de> Deserialize<'de> for DnsMessageHeader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HeaderJson::deserialize(deserializer)?
+            .into_header()
+            .map_err(D::Error::custom)
+    }
+}
+impl Serialize for DnsQuestion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        QuestionJson::new(self).serialize(serializer)
+    }
+}
+impl<'de> Deserialize<'de> for DnsQuestion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        QuestionJson::deserialize(deserializer)?
+            .into_question()
+            .map_err(D::Error::custom)
+    }
+}
+impl Serialize for DnsRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RecordJson::new(self)
+            .map_err(|e| serde::ser::Error::custom(format!("{e:?}")))?
+            .serialize(serializer)
+    }
+}
+impl<'de> Deserialize<'de> for DnsRecord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        RecordJson::deserialize(deserializer)?
+            .into_record()
+            .map_err(D::Error::custom)
+    }
+}
+impl Serialize for DnsMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MessageJson::new(self)
+            .map_err(|e| serde::ser::Error::custom(format!("{e:?}")))?
+            .serialize(serializer)
+    }
+}
+impl<'de> Deserialize<'de> for DnsMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        MessageJson::deserialize(deserializer)?
+            .into_message()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_rfc8427_example() {
+    // The example query from RFC 8427.
+    let message: DnsMessage = serde_json::from_str(
+        r#"{ "ID": 19678, "QR": 0, "Opcode": 0,
+          "AA": 0, "TC": 0, "RD": 0, "RA": 0, "AD": 0, "CD": 0, "RCODE": 0,
+          "QDCOUNT": 1, "ANCOUNT": 0, "NSCOUNT": 0, "ARCOUNT": 0,
+          "QNAME": "example.com", "QTYPE": 1, "QCLASS": 1 }"#,
+    )
+    .unwrap();
+    assert_eq!(19678, message.header.id);
+    assert!(!message.header.is_response);
+    assert_eq!(
+        vec![DnsQuestion {
+            name: DnsName::new("example.com").unwrap(),
+            typ: DnsType::A,
+            class: DnsClass::Internet,
+        }],
+        message.questions
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_record() {
+    let record = DnsRecord::new_cname("a.b", "c.d").unwrap().with_ttl(60);
+    assert_eq!(
+        r#"{"NAME":"a.b","TYPE":5,"TYPEname":"CNAME","CLASS":1,"CLASSname":"IN","TTL":60,"RDLENGTH":5,"RDATAHEX":"0163016400","rdataCNAME":"c.d."}"#,
+        serde_json::to_string(&record).unwrap()
+    );
+    assert_eq!(
+        record,
+        serde_json::from_str(r#"{"NAME":"a.b","TYPE":5,"TTL":60,"RDATAHEX":"0163016400"}"#)
+            .unwrap()
+    );
+    assert_eq!(
+        record,
+        serde_json::from_str(r#"{"NAME":"a.b","TYPEname":"CNAME","TTL":60,"rdataCNAME":"c.d"}"#)
+            .unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new_a("a.b", "10.0.0.1").unwrap(),
+        serde_json::from_str(r#"{"NAME":"a.b","TYPE":1,"rdataA":"10.0.0.1"}"#).unwrap()
+    );
+    let record = DnsRecord::new_mx("a.b", 10, "c.d").unwrap();
+    assert_eq!(
+        r#"{"NAME":"a.b","TYPE":15,"TYPEname":"MX","CLASS":1,"CLASSname":"IN","TTL":300,"RDLENGTH":7,"RDATAHEX":"000A0163016400","rdataMX":"10 c.d."}"#,
+        serde_json::to_string(&record).unwrap()
+    );
+    assert_eq!(
+        record,
+        serde_json::from_str(r#"{"NAME":"a.b","TYPEname":"MX","rdataMX":"10 c.d"}"#).unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new_ns("a.b", "ns1.a.b").unwrap(),
+        serde_json::from_str(r#"{"NAME":"a.b","TYPE":2,"rdataNS":"ns1.a.b."}"#).unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new_ptr("1.0.0.10.in-addr.arpa", "a.b").unwrap(),
+        serde_json::from_str(r#"{"NAME":"1.0.0.10.in-addr.arpa","TYPE":12,"rdataPTR":"a.b."}"#)
+            .unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new_soa("a.b", "ns1.a.b", "admin.a.b", 1, 2, 3, 4, 5).unwrap(),
+        serde_json::from_str(
+            r#"{"NAME":"a.b","TYPE":6,"rdataSOA":"ns1.a.b. admin.a.b. 1 2 3 4 5"}"#
+        )
+        .unwrap()
+    );
+    let record = DnsRecord::new_srv("_sip._tcp.a.b", 10, 60, 5060, "c.d").unwrap();
+    assert_eq!(
+        r#"{"NAME":"_sip._tcp.a.b","TYPE":33,"TYPEname":"SRV","CLASS":1,"CLASSname":"IN","TTL":300,"RDLENGTH":11,"RDATAHEX":"000A003C13C40163016400","rdataSRV":"10 60 5060 c.d."}"#,
+        serde_json::to_string(&record).unwrap()
+    );
+    assert_eq!(
+        record,
+        serde_json::from_str(
+            r#"{"NAME":"_sip._tcp.a.b","TYPEname":"SRV","rdataSRV":"10 60 5060 c.d"}"#
+        )
+        .unwrap()
+    );
+    let record = DnsRecord::new_txt("a.b", "v=spf1 -all").unwrap();
+    assert_eq!(
+        r#"{"NAME":"a.b","TYPE":16,"TYPEname":"TXT","CLASS":1,"CLASSname":"IN","TTL":300,"RDLENGTH":12,"RDATAHEX":"0B763D73706631202D616C6C","rdataTXT":"\"v=spf1 -all\""}"#,
+        serde_json::to_string(&record).unwrap()
+    );
+    assert_eq!(
+        record,
+        serde_json::from_str(r#"{"NAME":"a.b","TYPE":16,"rdataTXT":"\"v=spf1 -all\""}"#).unwrap()
+    );
+    // A string too long for a character-string has no wire form.
+    let record = DnsRecord::new(
+        DnsName::new("a.b").unwrap(),
+        DnsClass::Internet,
+        300,
+        DnsRecordData::TXT(vec![vec![b'a'; 256]]),
+    );
+    serde_json::to_string(&record).unwrap_err();
+    serde_json::from_str::<DnsRecord>(r#"{"NAME":"a.b","TYPE":1}"#).unwrap_err();
+    serde_json::from_str::<DnsRecord>(r#"{"NAME":"a.b","TYPE":1,"RDATAHEX":"0A00"}"#).unwrap_err();
+    serde_json::from_str::<DnsRecord>(
+        r#"{"NAME":"a.b","TYPE":1,"RDLENGTH":3,"RDATAHEX":"0A000001"}"#,
+    )
+    .unwrap_err();
+}
+
+#[cfg(test)]
+#[test]
+fn test_message_round_trip() {
+    let message = DnsMessage {
+        header: DnsMessageHeader {
+            id: 0x1234,
+            is_response: true,
+            op_code: DnsOpCode::Query,
+            authoritative_answer: true,
+            truncated: false,
+            recursion_desired: true,
+            recursion_available: false,
+            authentic_data: false,
+            checking_disabled: true,
+            response_code: DnsResponseCode::NoError,
+            question_count: 1,
+            answer_count: 1,
+            name_server_count: 0,
+            additional_count: 1,
+        },
+        questions: vec![DnsQuestion {
+            name: DnsName::new("a.b").unwrap(),
+            typ: DnsType::A,
+            class: DnsClass::Internet,
+        }],
+        answers: vec![DnsRecord::new_a("a.b", "10.0.0.1").unwrap()],
+        name_servers: Vec::new(),
+        additional: Vec::new(),
+        edns: Some(DnsEdns {
+            dnssec_ok: true,
+            ..DnsEdns::new(1232)
+        }),
+    };
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(1, json["QR"]);
+    assert_eq!(0, json["AD"]);
+    assert_eq!(1, json["CD"]);
+    assert_eq!("a.b", json["QNAME"]);
+    assert_eq!(41, json["additionalRRs"][0]["TYPE"]);
+    assert_eq!(1232, json["additionalRRs"][0]["CLASS"]);
+    assert_eq!(0x8000, json["additionalRRs"][0]["TTL"]);
+    assert_eq!(message, serde_json::from_value(json).unwrap());
+    // Counts come from the sections when missing, and flags may be booleans.
+    let parsed: DnsMessage = serde_json::from_str(
+        r#"{"ID": 1, "QR": true, "answerRRs": [{"NAME": "a.b", "TYPE": 1, "rdataA": "10.0.0.1"}]}"#,
+    )
+    .unwrap();
+    assert!(parsed.header.is_response);
+    assert_eq!(1, parsed.header.answer_count);
+    assert_eq!(0, parsed.header.question_count);
+    serde_json::from_str::<DnsMessage>(r#"{"Opcode": 16}"#).unwrap_err();
+    serde_json::from_str::<DnsMessage>(r#"{"QR": 2}"#).unwrap_err();
+}
+
+#[cfg(test)]
+#[test]
+fn test_header() {
+    let header: DnsMessageHeader = serde_json::from_str(r#"{"ID": 7, "RD": 1}"#).unwrap();
+    assert_eq!(7, header.id);
+    assert!(header.recursion_desired);
+    assert_eq!(
+        r#"{"ID":7,"QR":0,"Opcode":0,"AA":0,"TC":0,"RD":1,"RA":0,"AD":0,"CD":0,"RCODE":0,"QDCOUNT":0,"ANCOUNT":0,"NSCOUNT":0,"ARCOUNT":0}"#,
+        serde_json::to_string(&header).unwrap()
+    );
+}
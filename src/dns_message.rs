@@ -1,65 +1,357 @@
-use crate::{DnsError, DnsMessageHeader, DnsQuestion, DnsRecord, DnsResponseCode};
-use fixed_buffer::FixedBuf;
-use std::convert::TryFrom;
+use crate::dns_name_compressor::DnsNameCompressor;
+use crate::{
+    read_offset, DnsEdns, DnsError, DnsMessageBuilder, DnsMessageHeader, DnsParseError,
+    DnsQuestion, DnsRecord, DnsResponseCode, DnsSection, DnsType, FixedBuf,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+/// How [`DnsMessage`] treats invalid questions and records while reading.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ReadMode {
+    /// Stop at the first invalid part.
+    Strict,
+    /// Skip invalid additional records, except OPT records.
+    SkipInvalidAdditional,
+    /// Skip every invalid question and record whose end can be found.
+    Lenient,
+}
+impl ReadMode {
+    fn skips(self, section: DnsSection) -> bool {
+        match self {
+            ReadMode::Strict => false,
+            ReadMode::SkipInvalidAdditional => section == DnsSection::Additional,
+            ReadMode::Lenient => true,
+        }
+    }
+
+    fn skips_opt(self) -> bool {
+        self == ReadMode::Lenient
+    }
+}
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct DnsMessage {
     pub header: DnsMessageHeader,
     pub questions: Vec<DnsQuestion>,
     pub answers: Vec<DnsRecord>,
     pub name_servers: Vec<DnsRecord>,
     pub additional: Vec<DnsRecord>,
+    /// The OPT pseudo-record from the additional section.  It counts toward
+    /// `header.additional_count`.
+    pub edns: Option<DnsEdns>,
 }
 impl DnsMessage {
+    /// Starts building a standard query.
+    pub fn query(id: u16, question: DnsQuestion) -> DnsMessageBuilder {
+        DnsMessageBuilder::query(id, question)
+    }
+
+    /// Starts building a response to `request`, copying its ID, opcode, `RD` bit, `CD` bit, and
+    /// questions.
+    pub fn response_to(request: &DnsMessage) -> DnsMessageBuilder {
+        DnsMessageBuilder::response(&request.header, request.questions.clone())
+    }
+
+    /// Returns the header with the counts of the questions and records in the message.  These
+    /// are the counts that [`DnsMessage::write`] writes.
+    ///
+    /// # Errors
+    /// Returns an error when a section has more than 65,535 entries.
+    pub fn counted_header(&self) -> Result<DnsMessageHeader, DnsError> {
+        Ok(DnsMessageHeader {
+            question_count: self.question_count()?,
+            answer_count: u16::try_from(self.answers.len())
+                .map_err(|_| DnsError::TooManyAnswers)?,
+            name_server_count: u16::try_from(self.name_servers.len())
+                .map_err(|_| DnsError::TooManyNameServers)?,
+            additional_count: u16::try_from(
+                self.additional.len() + usize::from(self.edns.is_some()),
+            )
+            .map_err(|_| DnsError::TooManyAdditional)?,
+            ..self.header.clone()
+        })
+    }
+
     /// # Errors
     /// Returns an error when there are more than 65,536 questions.
     pub fn question_count(&self) -> Result<u16, DnsError> {
         u16::try_from(self.questions.len()).map_err(|_| DnsError::TooManyQuestions)
     }
 
+    /// Returns the response code, combining the 4 bits in the header with the upper 8 bits in the
+    /// OPT record, when there is one.
+    ///
+    /// > `EXTENDED-RCODE` Forms the upper 8 bits of extended 12-bit RCODE (together with the
+    /// > 4 bits defined in [RFC1035].  Note that EXTENDED-RCODE value 0 indicates that an
+    /// > unextended RCODE is in use (values 0 through 15).
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3>
+    #[must_use]
+    pub fn response_code(&self) -> DnsResponseCode {
+        Self::combine_response_code(&self.header, self.edns.as_ref())
+    }
+
+    pub(crate) fn combine_response_code(
+        header: &DnsMessageHeader,
+        edns: Option<&DnsEdns>,
+    ) -> DnsResponseCode {
+        let upper = edns.map_or(0, |edns| edns.extended_response_code);
+        DnsResponseCode::new(
+            (u16::from(upper) << 4) | u16::from(header.response_code.header_bits()),
+        )
+    }
+
+    /// Sets the response code, putting the lower 4 bits in the header and the upper 8 bits in
+    /// the OPT record.
+    ///
+    /// # Errors
+    /// Returns an error when `response_code` is above 15 and the message has no OPT record.
+    pub fn set_response_code(&mut self, response_code: DnsResponseCode) -> Result<(), DnsError> {
+        match &mut self.edns {
+            Some(edns) => edns.extended_response_code = response_code.extended_bits(),
+            None if response_code.extended_bits() != 0 => return Err(DnsError::NoOptRecord),
+            None => {}
+        }
+        self.header.response_code = DnsResponseCode::new(u16::from(response_code.header_bits()));
+        Ok(())
+    }
+
+    /// Reads a message, ignoring invalid additional records.  Use [`DnsMessage::read_strict`] or
+    /// [`DnsMessage::read_lenient`] to find out what is wrong with a message.
+    ///
     /// # Errors
     /// Returns an error when `buf` does not contain a valid message.
     pub fn read<const N: usize>(buf: &mut FixedBuf<N>) -> Result<Self, DnsError> {
-        let header = DnsMessageHeader::read(buf)?;
-        let mut questions = Vec::with_capacity(header.question_count as usize);
-        for _ in 0..header.question_count {
-            let question = DnsQuestion::read(buf)?;
-            questions.push(question);
-        }
-        let mut answers = Vec::with_capacity(header.answer_count as usize);
-        for _ in 0..header.answer_count {
-            let record = DnsRecord::read(buf)?;
-            answers.push(record);
-        }
-        let mut name_servers = Vec::with_capacity(header.name_server_count as usize);
-        for _ in 0..header.name_server_count {
-            let record = DnsRecord::read(buf)?;
-            name_servers.push(record);
-        }
-        let mut additional = Vec::with_capacity(header.additional_count as usize);
-        for _ in 0..header.additional_count {
-            #[allow(clippy::single_match)]
-            match DnsRecord::read(buf) {
-                Ok(record) => additional.push(record),
-                // Ignore invalid additional records.
-                Err(_) => {}
-            }
+        let (mut message, end) = Self::read_header(buf).map_err(|e| e.cause)?;
+        message
+            .read_sections(buf, end, ReadMode::SkipInvalidAdditional, &mut Vec::new())
+            .map_err(|e| e.cause)?;
+        Ok(message)
+    }
+
+    /// Reads a message, stopping at the first invalid part.
+    ///
+    /// # Errors
+    /// Returns an error saying which part of the message is invalid.
+    pub fn read_strict<const N: usize>(buf: &mut FixedBuf<N>) -> Result<Self, DnsParseError> {
+        let (mut message, end) = Self::read_header(buf)?;
+        message.read_sections(buf, end, ReadMode::Strict, &mut Vec::new())?;
+        Ok(message)
+    }
+
+    /// Reads a message, skipping invalid questions and records.  Returns the message and a
+    /// warning for each invalid part.  When a question or record is cut short or its name is
+    /// invalid, the rest of the message cannot be found, so this returns the parts read before
+    /// it.  The header counts are left as received.
+    ///
+    /// # Errors
+    /// Returns an error when `buf` does not contain a header.
+    pub fn read_lenient<const N: usize>(
+        buf: &mut FixedBuf<N>,
+    ) -> Result<(Self, Vec<DnsParseError>), DnsParseError> {
+        let (mut message, end) = Self::read_header(buf)?;
+        let mut warnings = Vec::new();
+        if let Err(e) = message.read_sections(buf, end, ReadMode::Lenient, &mut warnings) {
+            warnings.push(e);
         }
-        Ok(Self {
+        Ok((message, warnings))
+    }
+
+    /// Reads the header and returns a message with empty sections, and the offset of the end of
+    /// the message.
+    fn read_header<const N: usize>(buf: &mut FixedBuf<N>) -> Result<(Self, usize), DnsParseError> {
+        let offset = read_offset(buf);
+        let end = offset + buf.len();
+        let header = DnsMessageHeader::read(buf).map_err(|cause| DnsParseError {
+            section: DnsSection::Header,
+            index: 0,
+            offset,
+            cause,
+        })?;
+        let message = Self {
             header,
-            questions,
-            answers,
-            name_servers,
-            additional,
-        })
+            questions: Vec::new(),
+            answers: Vec::new(),
+            name_servers: Vec::new(),
+            additional: Vec::new(),
+            edns: None,
+        };
+        Ok((message, end))
+    }
+
+    /// Reads the sections counted in the header.  `end` is the offset of the end of the message.
+    fn read_sections<const N: usize>(
+        &mut self,
+        buf: &mut FixedBuf<N>,
+        end: usize,
+        mode: ReadMode,
+        warnings: &mut Vec<DnsParseError>,
+    ) -> Result<(), DnsParseError> {
+        let skip = |section| mode.skips(section);
+        for index in 0..usize::from(self.header.question_count) {
+            let section = DnsSection::Question;
+            let item = Self::read_item(
+                buf,
+                end,
+                section,
+                index,
+                skip(section),
+                warnings,
+                DnsQuestion::read,
+            )?;
+            self.questions.extend(item);
+        }
+        for (section, count) in [
+            (DnsSection::Answer, self.header.answer_count),
+            (DnsSection::Authority, self.header.name_server_count),
+        ] {
+            for index in 0..usize::from(count) {
+                let item = Self::read_item(
+                    buf,
+                    end,
+                    section,
+                    index,
+                    skip(section),
+                    warnings,
+                    DnsRecord::read,
+                )?;
+                match section {
+                    DnsSection::Answer => self.answers.extend(item),
+                    _ => self.name_servers.extend(item),
+                }
+            }
+        }
+        let section = DnsSection::Additional;
+        for index in 0..usize::from(self.header.additional_count) {
+            if Self::peek_record_type(buf).is_ok_and(|typ| typ == DnsType::OPT) {
+                let edns = &mut self.edns;
+                let item = Self::read_item(
+                    buf,
+                    end,
+                    section,
+                    index,
+                    mode.skips_opt(),
+                    warnings,
+                    |buf| {
+                        // > If a query message with more than one OPT RR is received, a FORMERR
+                        // > (RCODE=1) MUST be returned.
+                        // https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.1
+                        if edns.is_some() {
+                            return Err(DnsError::TooManyOptRecords);
+                        }
+                        DnsEdns::read(buf)
+                    },
+                )?;
+                if item.is_some() {
+                    *edns = item;
+                }
+                continue;
+            }
+            let item = Self::read_item(
+                buf,
+                end,
+                section,
+                index,
+                skip(section),
+                warnings,
+                DnsRecord::read,
+            )?;
+            self.additional.extend(item);
+        }
+        Ok(())
     }
 
+    /// Reads one question or record with `read`.  When the item is invalid and `skip` is true,
+    /// moves past it, adds a warning, and returns `None`.
+    ///
     /// # Errors
-    /// Returns an error when `buf` fills up.
+    /// Returns an error when the item is invalid and `skip` is false, or when the end of the item
+    /// cannot be found.
+    fn read_item<const N: usize, T>(
+        buf: &mut FixedBuf<N>,
+        end: usize,
+        section: DnsSection,
+        index: usize,
+        skip: bool,
+        warnings: &mut Vec<DnsParseError>,
+        read: impl FnOnce(&mut FixedBuf<N>) -> Result<T, DnsError>,
+    ) -> Result<Option<T>, DnsParseError> {
+        // `FixedBuf` moves back to offset 0 when it is empty, so count from the end.
+        let offset = end - buf.len();
+        let error = |cause| DnsParseError {
+            section,
+            index,
+            offset,
+            cause,
+        };
+        let len = Self::peek_len(buf.readable(), section).map_err(error)?;
+        let unread = buf.len();
+        match read(buf) {
+            Ok(item) => Ok(Some(item)),
+            Err(cause) if skip => {
+                buf.read_bytes(len.saturating_sub(unread - buf.len()));
+                warnings.push(error(cause));
+                Ok(None)
+            }
+            Err(cause) => Err(error(cause)),
+        }
+    }
+
+    /// Returns the length of the name at the start of `bytes`, without following pointers.
+    fn peek_name_len(bytes: &[u8]) -> Result<usize, DnsError> {
+        let mut index = 0;
+        loop {
+            let len = *bytes.get(index).ok_or(DnsError::Truncated)?;
+            match len & 0xC0 {
+                0x00 if len == 0 => return Ok(index + 1),
+                0x00 => index += 1 + usize::from(len),
+                0xC0 => return Ok(index + 2),
+                _ => return Err(DnsError::InvalidLabel),
+            }
+        }
+    }
+
+    /// Returns the length of the question or resource record at the start of `bytes`.
+    fn peek_len(bytes: &[u8], section: DnsSection) -> Result<usize, DnsError> {
+        let name_len = Self::peek_name_len(bytes)?;
+        let len = if section == DnsSection::Question {
+            name_len + 4
+        } else {
+            let rdata_len = bytes
+                .get(name_len + 8..name_len + 10)
+                .ok_or(DnsError::Truncated)?;
+            name_len + 10 + usize::from(u16::from_be_bytes([rdata_len[0], rdata_len[1]]))
+        };
+        if bytes.len() < len {
+            return Err(DnsError::Truncated);
+        }
+        Ok(len)
+    }
+
+    /// Returns the type of the resource record at the start of `buf`, without consuming it.
+    fn peek_record_type<const N: usize>(buf: &FixedBuf<N>) -> Result<DnsType, DnsError> {
+        let bytes = buf.readable();
+        let index = Self::peek_name_len(bytes)?;
+        let typ = bytes.get(index..index + 2).ok_or(DnsError::Truncated)?;
+        Ok(DnsType::new(u16::from_be_bytes([typ[0], typ[1]])))
+    }
+
+    /// Writes the message, compressing names that repeat a suffix of an earlier name.  The header
+    /// counts come from [`DnsMessage::counted_header`].
+    ///
+    /// # Errors
+    /// Returns an error when `buf` fills up or a section has more than 65,535 entries.
     pub fn write<const N: usize>(&self, out: &mut FixedBuf<N>) -> Result<(), DnsError> {
-        self.header.write(out)?;
+        let mut compressor = DnsNameCompressor::new(out);
+        self.counted_header()?.write(out)?;
         for question in &self.questions {
-            question.write(out)?;
+            question.write_compressed(out, &mut compressor)?;
         }
         for record in self
             .answers
@@ -67,63 +359,608 @@ impl DnsMessage {
             .chain(self.name_servers.iter())
             .chain(self.additional.iter())
         {
-            record.write(out)?;
+            record.write_compressed(out, &mut compressor)?;
+        }
+        if let Some(edns) = &self.edns {
+            edns.write(out)?;
         }
         Ok(())
     }
 
+    /// Removes the last RRset, looking in the additional section first, then the authority
+    /// section, then the answer section.  Use this to make a response fit a size limit.
+    ///
+    /// > When a DNS client receives a reply with TC set, it should ignore that response, and query
+    /// > again, using a mechanism, such as a TCP connection, that will permit larger replies.
+    /// >
+    /// > The TC bit should not be set merely because some extra information could have been
+    /// > included in a message but there was insufficient room.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2181#section-9>
+    ///
+    /// So this sets the `truncated` header bit only when it removes an answer or authority
+    /// RRset.  The OPT record is never removed.  Returns `false` when there is nothing left to
+    /// remove.
+    pub fn drop_last_rrset(&mut self) -> bool {
+        fn drop_last(records: &mut Vec<DnsRecord>) -> bool {
+            let Some(last) = records.last() else {
+                return false;
+            };
+            let (name, typ) = (last.name().clone(), last.typ());
+            records.retain(|record| record.name() != &name || record.typ() != typ);
+            true
+        }
+        if drop_last(&mut self.additional) {
+            true
+        } else if drop_last(&mut self.name_servers) || drop_last(&mut self.answers) {
+            self.header.truncated = true;
+            true
+        } else {
+            false
+        }
+    }
+
     /// # Errors
-    /// Returns an error when there are more than 65,536 questions.
+    /// Returns an error when there are more than 65,536 questions or answers.
     pub fn answer_response<'x>(
         &self,
         answers: impl Iterator<Item = &'x DnsRecord>,
     ) -> Result<Self, DnsError> {
-        let answers: Vec<DnsRecord> = answers.cloned().collect();
-        let answer_count = u16::try_from(answers.len()).map_err(|_| DnsError::TooManyAnswers)?;
-        Ok(Self {
-            header: DnsMessageHeader {
-                id: self.header.id,
-                is_response: true,
-                op_code: self.header.op_code,
-                authoritative_answer: true,
-                truncated: false,
-                recursion_desired: self.header.recursion_desired,
-                recursion_available: false,
-                response_code: DnsResponseCode::NoError,
-                question_count: self.question_count()?,
-                answer_count,
-                name_server_count: 0,
-                additional_count: 0,
-            },
-            questions: self.questions.clone(),
-            answers,
-            name_servers: Vec::new(),
-            additional: Vec::new(),
-        })
+        Self::response(
+            &self.header,
+            self.questions.clone(),
+            DnsResponseCode::NoError,
+            answers.cloned().collect(),
+        )
     }
 
     /// # Errors
     /// Returns an error when there are more than 65,536 questions.
     pub fn error_response(&self, response_code: DnsResponseCode) -> Result<Self, DnsError> {
-        Ok(Self {
-            header: DnsMessageHeader {
-                id: self.header.id,
-                is_response: true,
-                op_code: self.header.op_code,
-                authoritative_answer: true,
-                truncated: false,
-                recursion_desired: self.header.recursion_desired,
-                recursion_available: false,
-                response_code,
-                question_count: self.question_count()?,
-                answer_count: 0,
-                name_server_count: 0,
-                additional_count: 0,
+        Self::response(
+            &self.header,
+            self.questions.clone(),
+            response_code,
+            Vec::new(),
+        )
+    }
+
+    /// Makes an authoritative response to the request with `request_header`.
+    pub(crate) fn response(
+        request_header: &DnsMessageHeader,
+        questions: Vec<DnsQuestion>,
+        response_code: DnsResponseCode,
+        answers: Vec<DnsRecord>,
+    ) -> Result<Self, DnsError> {
+        DnsMessageBuilder::response(request_header, questions)
+            .authoritative_answer(true)
+            .response_code(response_code)
+            .answers(answers)
+            .build()
+    }
+}
+/// Writes the message like dig does:
+///
+/// ```text
+/// ;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4660
+/// ;; flags: qr aa rd; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 0
+///
+/// ;; QUESTION SECTION:
+/// ;a.b.    IN  A
+///
+/// ;; ANSWER SECTION:
+/// a.b.    300 IN  A   10.0.0.1
+/// ```
+///
+/// Empty sections are left out.  The status is the whole response code, including the upper bits
+/// from the OPT record.
+impl Display for DnsMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        let header = DnsMessageHeader {
+            response_code: self.response_code(),
+            ..self
+                .counted_header()
+                .unwrap_or_else(|_| self.header.clone())
+        };
+        write!(f, "{header}")?;
+        if let Some(edns) = &self.edns {
+            let edns = DnsEdns {
+                extended_response_code: 0,
+                ..edns.clone()
+            };
+            write!(f, "\n\n;; OPT PSEUDOSECTION:\n{edns}")?;
+        }
+        if !self.questions.is_empty() {
+            write!(f, "\n\n;; QUESTION SECTION:")?;
+            for question in &self.questions {
+                write!(f, "\n;{question}")?;
+            }
+        }
+        for (title, records) in [
+            ("ANSWER", &self.answers),
+            ("AUTHORITY", &self.name_servers),
+            ("ADDITIONAL", &self.additional),
+        ] {
+            if !records.is_empty() {
+                write!(f, "\n\n;; {title} SECTION:")?;
+                for record in records {
+                    write!(f, "\n{record}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+/// Parses a message in the form written by [`Display`], which is also the form dig prints.
+///
+/// The header lines are optional.  The header counts are set from the parsed sections, not from
+/// the text.  The status sets the whole response code, so codes above 15 need an OPT
+/// pseudo-section.  Other lines starting with `;` are comments, except in the OPT pseudo-section and the
+/// question section.
+impl FromStr for DnsMessage {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        enum Section {
+            None,
+            Opt,
+            Question,
+            Answer,
+            Authority,
+            Additional,
+        }
+        let mut section = Section::None;
+        let mut header_lines = String::new();
+        let mut edns_lines = String::new();
+        let mut questions = Vec::new();
+        let mut answers = Vec::new();
+        let mut name_servers = Vec::new();
+        let mut additional = Vec::new();
+        for line in value.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.starts_with(";; ->>HEADER<<-") || line.starts_with(";; flags:") {
+                header_lines.push_str(line);
+                header_lines.push('\n');
+                continue;
+            }
+            match line {
+                ";; OPT PSEUDOSECTION:" => section = Section::Opt,
+                ";; QUESTION SECTION:" => section = Section::Question,
+                ";; ANSWER SECTION:" => section = Section::Answer,
+                ";; AUTHORITY SECTION:" => section = Section::Authority,
+                ";; ADDITIONAL SECTION:" => section = Section::Additional,
+                _ if line.starts_with(";;") => {}
+                _ => match section {
+                    Section::None if line.starts_with(';') => {}
+                    Section::None => return Err(format!("line is not in a section: {line:?}")),
+                    Section::Opt => {
+                        edns_lines.push_str(line);
+                        edns_lines.push('\n');
+                    }
+                    Section::Question => questions.push(line.parse()?),
+                    _ if line.starts_with(';') => {}
+                    Section::Answer => answers.push(line.parse()?),
+                    Section::Authority => name_servers.push(line.parse()?),
+                    Section::Additional => additional.push(line.parse()?),
+                },
+            }
+        }
+        let edns: Option<DnsEdns> = if edns_lines.is_empty() {
+            None
+        } else {
+            Some(edns_lines.parse()?)
+        };
+        let header: DnsMessageHeader = header_lines.parse()?;
+        let response_code = header.response_code;
+        let mut message = Self {
+            header,
+            questions,
+            answers,
+            name_servers,
+            additional,
+            edns,
+        };
+        message
+            .set_response_code(response_code)
+            .map_err(|e| format!("status {response_code} needs an OPT record: {e:?}"))?;
+        message.header = message
+            .counted_header()
+            .map_err(|e| format!("{e:?}: {value:?}"))?;
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_write_compressed() {
+    use crate::{DnsClass, DnsName, DnsOpCode, DnsType};
+    let message = DnsMessage {
+        header: DnsMessageHeader {
+            id: 0x1234,
+            is_response: true,
+            op_code: DnsOpCode::Query,
+            authoritative_answer: true,
+            truncated: false,
+            recursion_desired: false,
+            recursion_available: false,
+            authentic_data: false,
+            checking_disabled: false,
+            response_code: DnsResponseCode::NoError,
+            question_count: 1,
+            answer_count: 3,
+            name_server_count: 0,
+            additional_count: 0,
+        },
+        questions: vec![DnsQuestion {
+            name: DnsName::new("www.example.com").unwrap(),
+            typ: DnsType::A,
+            class: DnsClass::Internet,
+        }],
+        answers: vec![
+            DnsRecord::new_cname("www.example.com", "web.example.com").unwrap(),
+            DnsRecord::new_a("web.example.com", "10.0.0.1").unwrap(),
+            DnsRecord::new_a("web.example.com", "10.0.0.2").unwrap(),
+        ],
+        name_servers: Vec::new(),
+        additional: Vec::new(),
+        edns: None,
+    };
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    message.write(&mut out).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(
+        [
+            0x12, 0x34, 0x84, 0x00, 0, 1, 0, 3, 0, 0, 0, 0,
+            // 12: www.example.com A IN
+            3, b'w', b'w', b'w', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0,
+            0, 1, 0, 1,
+            // 33: www.example.com CNAME web.example.com
+            0xC0, 12, 0, 5, 0, 1, 0, 0, 1, 0x2C, 0, 6, 3, b'w', b'e', b'b', 0xC0, 16,
+            // 51: web.example.com A 10.0.0.1
+            0xC0, 45, 0, 1, 0, 1, 0, 0, 1, 0x2C, 0, 4, 10, 0, 0, 1,
+            // 67: web.example.com A 10.0.0.2
+            0xC0, 45, 0, 1, 0, 1, 0, 0, 1, 0x2C, 0, 4, 10, 0, 0, 2,
+        ],
+        out.readable()
+    );
+    assert_eq!(message, DnsMessage::read(&mut out).unwrap());
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_duplicate_opt() {
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[
+        0x12, 0x34, 0x00, 0x00, 0, 0, 0, 0, 0, 0, 0, 2, // header
+        0, 0, 41, 2, 0, 0, 0, 0, 0, 0, 0, // OPT
+        0, 0, 41, 2, 0, 0, 0, 0, 0, 0, 0, // OPT
+    ])
+    .unwrap();
+    assert_eq!(Err(DnsError::TooManyOptRecords), DnsMessage::read(&mut buf));
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_strict_lenient() {
+    use crate::{DnsClass, DnsName, DnsRecordData};
+    use core::net::Ipv4Addr;
+    let bytes = [
+        0x12, 0x34, 0x84, 0x00, 0, 1, 0, 2, 0, 0, 0, 1, // header
+        // 12: a A IN
+        1, b'a', 0, 0, 1, 0, 1, //
+        // 19: a 60 CH A 10.0.0.1
+        0xC0, 12, 0, 1, 0, 3, 0, 0, 0, 60, 0, 4, 10, 0, 0, 1, //
+        // 35: a 60 IN A 10.0.0.2
+        0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 10, 0, 0, 2, //
+        // 51: a 60 IN A with 8 bytes of RDATA, cut short
+        0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 8, 10, 0, 0, 3,
+    ];
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&bytes).unwrap();
+    assert_eq!(
+        Err(DnsError::InvalidClass),
+        DnsMessage::read(&mut buf.clone())
+    );
+    assert_eq!(
+        Err(DnsParseError {
+            section: DnsSection::Answer,
+            index: 0,
+            offset: 19,
+            cause: DnsError::InvalidClass,
+        }),
+        DnsMessage::read_strict(&mut buf.clone())
+    );
+    let (message, warnings) = DnsMessage::read_lenient(&mut buf).unwrap();
+    assert_eq!(
+        vec![
+            DnsParseError {
+                section: DnsSection::Answer,
+                index: 0,
+                offset: 19,
+                cause: DnsError::InvalidClass,
             },
-            questions: self.questions.clone(),
-            answers: Vec::new(),
-            name_servers: Vec::new(),
-            additional: Vec::new(),
-        })
+            DnsParseError {
+                section: DnsSection::Additional,
+                index: 0,
+                offset: 51,
+                cause: DnsError::Truncated,
+            },
+        ],
+        warnings
+    );
+    assert_eq!(1, message.questions.len());
+    assert_eq!(
+        vec![DnsRecord::new(
+            DnsName::new("a").unwrap(),
+            DnsClass::Internet,
+            60,
+            DnsRecordData::A(Ipv4Addr::new(10, 0, 0, 2)),
+        )],
+        message.answers
+    );
+    assert!(message.additional.is_empty());
+    assert_eq!(2, message.header.answer_count);
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_invalid_additional() {
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[
+        0x12, 0x34, 0x00, 0x00, 0, 0, 0, 0, 0, 0, 0, 3, // header
+        // 12: a 60 CH A 10.0.0.1
+        1, b'a', 0, 0, 1, 0, 3, 0, 0, 0, 60, 0, 4, 10, 0, 0, 1, //
+        // 29: OPT with an option cut short
+        0, 0, 41, 2, 0, 0, 0, 0, 0, 0, 2, 0, 10, //
+        // 42: a 60 IN A 10.0.0.2
+        0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 10, 0, 0, 2,
+    ])
+    .unwrap();
+    assert_eq!(
+        Err(DnsError::InvalidRdata),
+        DnsMessage::read(&mut buf.clone())
+    );
+    assert_eq!(
+        Err(DnsParseError {
+            section: DnsSection::Additional,
+            index: 0,
+            offset: 12,
+            cause: DnsError::InvalidClass,
+        }),
+        DnsMessage::read_strict(&mut buf.clone())
+    );
+    let (message, warnings) = DnsMessage::read_lenient(&mut buf).unwrap();
+    assert_eq!(
+        vec![
+            (0, 12, DnsError::InvalidClass),
+            (1, 29, DnsError::InvalidRdata)
+        ],
+        warnings
+            .into_iter()
+            .map(|w| (w.index, w.offset, w.cause))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(None, message.edns);
+    assert_eq!(1, message.additional.len());
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_lenient_skips_last_question() {
+    // Found by fuzzing.  The invalid question is the last byte in the buffer.
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[
+        0x0A, 0, 0, 0, 0x3F, 0, 0, 0, 0, 0, 0, 0x28, // header
+        0, 0, 0, 0xFF, 0, // 12: . TYPE0 CLASS65280
+    ])
+    .unwrap();
+    let (message, warnings) = DnsMessage::read_lenient(&mut buf).unwrap();
+    assert!(message.questions.is_empty());
+    assert_eq!(
+        vec![
+            (0, 12, DnsError::InvalidClass),
+            (1, 17, DnsError::Truncated)
+        ],
+        warnings
+            .into_iter()
+            .map(|w| (w.index, w.offset, w.cause))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[cfg(all(test, feature = "arbitrary"))]
+#[test]
+fn test_arbitrary_write_read() {
+    use arbitrary::{Arbitrary, Unstructured};
+    let mut seed = 1_u32;
+    let mut count = 0;
+    for _ in 0..1000 {
+        let bytes: Vec<u8> = (0..2000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                seed.to_be_bytes()[1]
+            })
+            .collect();
+        let Ok(message) = DnsMessage::arbitrary(&mut Unstructured::new(&bytes)) else {
+            continue;
+        };
+        let mut buf: FixedBuf<65535> = FixedBuf::new();
+        if message.write(&mut buf).is_err() {
+            continue;
+        }
+        let expected = DnsMessage {
+            header: message.counted_header().unwrap(),
+            ..message
+        };
+        assert_eq!(Ok(expected), DnsMessage::read_strict(&mut buf));
+        count += 1;
     }
+    assert!(count > 100, "{count}");
+}
+
+#[cfg(test)]
+#[test]
+fn test_drop_last_rrset() {
+    use crate::DnsOpCode;
+    let mut message = DnsMessage {
+        header: DnsMessageHeader {
+            id: 0x1234,
+            is_response: true,
+            op_code: DnsOpCode::Query,
+            authoritative_answer: true,
+            truncated: false,
+            recursion_desired: false,
+            recursion_available: false,
+            authentic_data: false,
+            checking_disabled: false,
+            response_code: DnsResponseCode::NoError,
+            question_count: 0,
+            answer_count: 3,
+            name_server_count: 0,
+            additional_count: 2,
+        },
+        questions: Vec::new(),
+        answers: vec![
+            DnsRecord::new_a("a.b", "10.0.0.1").unwrap(),
+            DnsRecord::new_aaaa("a.b", "::1").unwrap(),
+            DnsRecord::new_a("a.b", "10.0.0.2").unwrap(),
+        ],
+        name_servers: Vec::new(),
+        additional: vec![DnsRecord::new_cname("c.d", "e.f").unwrap()],
+        edns: Some(DnsEdns::new(1232)),
+    };
+    assert!(message.drop_last_rrset());
+    assert!(message.additional.is_empty());
+    assert_eq!(1, message.counted_header().unwrap().additional_count);
+    assert!(!message.header.truncated);
+    assert!(message.drop_last_rrset());
+    assert_eq!(
+        vec![DnsRecord::new_aaaa("a.b", "::1").unwrap()],
+        message.answers
+    );
+    assert_eq!(1, message.counted_header().unwrap().answer_count);
+    assert!(message.header.truncated);
+    assert!(message.drop_last_rrset());
+    assert!(!message.drop_last_rrset());
+    assert_eq!(0, message.counted_header().unwrap().answer_count);
+    assert_eq!(Some(DnsEdns::new(1232)), message.edns);
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    use crate::{DnsClass, DnsName, DnsOpCode};
+    let message = DnsMessage {
+        header: DnsMessageHeader {
+            id: 0x1234,
+            is_response: true,
+            op_code: DnsOpCode::Query,
+            authoritative_answer: true,
+            truncated: false,
+            recursion_desired: true,
+            recursion_available: false,
+            authentic_data: false,
+            checking_disabled: false,
+            response_code: DnsResponseCode::NoError,
+            question_count: 1,
+            answer_count: 2,
+            name_server_count: 0,
+            additional_count: 2,
+        },
+        questions: vec![DnsQuestion {
+            name: DnsName::new("www.example.com").unwrap(),
+            typ: DnsType::A,
+            class: DnsClass::Internet,
+        }],
+        answers: vec![
+            DnsRecord::new_cname("www.example.com", "web.example.com").unwrap(),
+            DnsRecord::new_a("web.example.com", "10.0.0.1").unwrap(),
+        ],
+        name_servers: Vec::new(),
+        additional: vec![DnsRecord::new_aaaa("web.example.com", "::1").unwrap()],
+        edns: Some(DnsEdns::new(1232)),
+    };
+    let text = "\
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4660
+;; flags: qr aa rd; QUERY: 1, ANSWER: 2, AUTHORITY: 0, ADDITIONAL: 2
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags:; udp: 1232
+
+;; QUESTION SECTION:
+;www.example.com.\tIN\tA
+
+;; ANSWER SECTION:
+www.example.com.\t300\tIN\tCNAME\tweb.example.com.
+web.example.com.\t300\tIN\tA\t10.0.0.1
+
+;; ADDITIONAL SECTION:
+web.example.com.\t300\tIN\tAAAA\t::1";
+    assert_eq!(text, message.to_string());
+    assert_eq!(Ok(message.clone()), text.parse());
+    // dig output, with comments and counts that do not match.
+    let parsed: DnsMessage = "\
+; <<>> DiG 9.18.18 <<>> @127.0.0.1 www.example.com
+;; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4660
+;; flags: qr aa rd; QUERY: 9, ANSWER: 9, AUTHORITY: 9, ADDITIONAL: 9
+
+;; QUESTION SECTION:
+;www.example.com.\t\tIN\tA
+
+;; ANSWER SECTION:
+www.example.com.\t300\tIN\tCNAME\tweb.example.com.
+
+;; Query time: 0 msec"
+        .parse()
+        .unwrap();
+    assert_eq!(1, parsed.header.question_count);
+    assert_eq!(1, parsed.header.answer_count);
+    assert_eq!(0, parsed.header.additional_count);
+    assert_eq!(message.answers[..1], parsed.answers[..]);
+    // Without the header lines.
+    let parsed: DnsMessage = ";; ANSWER SECTION:\na.b A 10.0.0.1".parse().unwrap();
+    assert_eq!(0, parsed.header.id);
+    assert_eq!(
+        vec![DnsRecord::new_a("a.b", "10.0.0.1").unwrap()],
+        parsed.answers
+    );
+    "a.b A 10.0.0.1".parse::<DnsMessage>().unwrap_err();
+    ";; ANSWER SECTION:\na.b A x"
+        .parse::<DnsMessage>()
+        .unwrap_err();
+}
+
+#[cfg(test)]
+#[test]
+fn test_response_code() {
+    let mut message: DnsMessage = ";; ->>HEADER<<- opcode: QUERY, status: NXDOMAIN, id: 1"
+        .parse()
+        .unwrap();
+    assert_eq!(DnsResponseCode::NameError, message.response_code());
+    assert_eq!(
+        Err(DnsError::NoOptRecord),
+        message.set_response_code(DnsResponseCode::BadCookie)
+    );
+    message.edns = Some(DnsEdns::new(1232));
+    message.header.additional_count = 1;
+    message
+        .set_response_code(DnsResponseCode::BadCookie)
+        .unwrap();
+    assert_eq!(DnsResponseCode::YxRrSet, message.header.response_code);
+    assert_eq!(1, message.edns.as_ref().unwrap().extended_response_code);
+    assert_eq!(DnsResponseCode::BadCookie, message.response_code());
+    let text = message.to_string();
+    assert!(text.starts_with(";; ->>HEADER<<- opcode: QUERY, status: BADCOOKIE, id: 1\n"));
+    // The OPT pseudo-section leaves out the upper bits, since the status has them.
+    assert!(text.ends_with("\n; EDNS: version: 0, flags:; udp: 1232"));
+    assert_eq!(Ok(message.clone()), text.parse());
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    message.write(&mut out).unwrap();
+    assert_eq!(message, DnsMessage::read(&mut out).unwrap());
+    message.set_response_code(DnsResponseCode::Refused).unwrap();
+    assert_eq!(0, message.edns.as_ref().unwrap().extended_response_code);
+    assert_eq!(DnsResponseCode::Refused, message.response_code());
+    ";; ->>HEADER<<- opcode: QUERY, status: BADVERS, id: 1"
+        .parse::<DnsMessage>()
+        .unwrap_err();
 }
@@ -0,0 +1,267 @@
+use crate::{
+    DnsEdns, DnsEdnsOption, DnsError, DnsMessage, DnsMessageHeader, DnsOpCode, DnsQuestion,
+    DnsRecord, DnsResponseCode,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The UDP payload size advertised by an OPT record that the builder adds on its own.  This is
+/// the size recommended by DNS Flag Day 2020.
+///
+/// <https://www.dnsflagday.net/2020/>
+const DEFAULT_UDP_PAYLOAD_SIZE: u16 = 1232;
+
+/// Builds a [`DnsMessage`].  Start with [`DnsMessage::query`] or [`DnsMessage::response_to`].
+///
+/// The header counts are not set by hand.  [`DnsMessageBuilder::build`] sets them from the
+/// sections, and [`DnsMessage::write`] recomputes them.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct DnsMessageBuilder {
+    message: DnsMessage,
+    response_code: DnsResponseCode,
+}
+impl DnsMessageBuilder {
+    fn new(header: DnsMessageHeader, questions: Vec<DnsQuestion>) -> Self {
+        let response_code = header.response_code;
+        Self {
+            message: DnsMessage {
+                header,
+                questions,
+                answers: Vec::new(),
+                name_servers: Vec::new(),
+                additional: Vec::new(),
+                edns: None,
+            },
+            response_code,
+        }
+    }
+
+    /// Starts a standard query with `id` and `question`.
+    pub(crate) fn query(id: u16, question: DnsQuestion) -> Self {
+        let mut header = DnsMessageHeader::from_bytes([0; 12]);
+        header.id = id;
+        Self::new(header, vec![question])
+    }
+
+    /// Starts a response to the request with `request_header`, which asked `questions`.  Copies
+    /// the ID, opcode, `RD` bit, and `CD` bit from the request.
+    pub(crate) fn response(request_header: &DnsMessageHeader, questions: Vec<DnsQuestion>) -> Self {
+        let mut header = DnsMessageHeader::from_bytes([0; 12]);
+        header.id = request_header.id;
+        header.is_response = true;
+        header.op_code = request_header.op_code;
+        header.recursion_desired = request_header.recursion_desired;
+        // > The CD bit is controlled by resolvers; a security-aware name server MUST copy the CD
+        // > bit from a query into the corresponding response.
+        // https://datatracker.ietf.org/doc/html/rfc4035#section-3.2.2
+        header.checking_disabled = request_header.checking_disabled;
+        Self::new(header, questions)
+    }
+
+    pub fn id(mut self, id: u16) -> Self {
+        self.message.header.id = id;
+        self
+    }
+
+    pub fn op_code(mut self, op_code: DnsOpCode) -> Self {
+        self.message.header.op_code = op_code;
+        self
+    }
+
+    pub fn authoritative_answer(mut self, value: bool) -> Self {
+        self.message.header.authoritative_answer = value;
+        self
+    }
+
+    pub fn truncated(mut self, value: bool) -> Self {
+        self.message.header.truncated = value;
+        self
+    }
+
+    pub fn recursion_desired(mut self, value: bool) -> Self {
+        self.message.header.recursion_desired = value;
+        self
+    }
+
+    pub fn recursion_available(mut self, value: bool) -> Self {
+        self.message.header.recursion_available = value;
+        self
+    }
+
+    pub fn authentic_data(mut self, value: bool) -> Self {
+        self.message.header.authentic_data = value;
+        self
+    }
+
+    pub fn checking_disabled(mut self, value: bool) -> Self {
+        self.message.header.checking_disabled = value;
+        self
+    }
+
+    /// Sets the whole response code.  Codes above 15 need an OPT record.
+    pub fn response_code(mut self, response_code: DnsResponseCode) -> Self {
+        self.response_code = response_code;
+        self
+    }
+
+    pub fn question(mut self, question: DnsQuestion) -> Self {
+        self.message.questions.push(question);
+        self
+    }
+
+    pub fn answer(mut self, record: DnsRecord) -> Self {
+        self.message.answers.push(record);
+        self
+    }
+
+    pub fn answers(mut self, records: impl IntoIterator<Item = DnsRecord>) -> Self {
+        self.message.answers.extend(records);
+        self
+    }
+
+    pub fn name_server(mut self, record: DnsRecord) -> Self {
+        self.message.name_servers.push(record);
+        self
+    }
+
+    pub fn name_servers(mut self, records: impl IntoIterator<Item = DnsRecord>) -> Self {
+        self.message.name_servers.extend(records);
+        self
+    }
+
+    pub fn additional(mut self, record: DnsRecord) -> Self {
+        self.message.additional.push(record);
+        self
+    }
+
+    /// Sets the OPT record, replacing any earlier one.
+    pub fn edns(mut self, edns: DnsEdns) -> Self {
+        self.message.edns = Some(edns);
+        self
+    }
+
+    /// Returns the OPT record, adding one that advertises 1232 bytes when there is none.
+    fn edns_mut(&mut self) -> &mut DnsEdns {
+        self.message
+            .edns
+            .get_or_insert_with(|| DnsEdns::new(DEFAULT_UDP_PAYLOAD_SIZE))
+    }
+
+    pub fn udp_payload_size(mut self, udp_payload_size: u16) -> Self {
+        self.edns_mut().udp_payload_size = udp_payload_size;
+        self
+    }
+
+    pub fn dnssec_ok(mut self, value: bool) -> Self {
+        self.edns_mut().dnssec_ok = value;
+        self
+    }
+
+    pub fn edns_option(mut self, option: DnsEdnsOption) -> Self {
+        self.edns_mut().options.push(option);
+        self
+    }
+
+    /// # Errors
+    /// Returns an error when the response code is above 15 and the message has no OPT record, or
+    /// when a section has more than 65,535 entries.
+    pub fn build(self) -> Result<DnsMessage, DnsError> {
+        let mut message = self.message;
+        message.set_response_code(self.response_code)?;
+        message.header = message.counted_header()?;
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_query() {
+    use crate::FixedBuf;
+    use crate::{DnsClass, DnsName, DnsType};
+    let question = DnsQuestion {
+        name: DnsName::new("a.b").unwrap(),
+        typ: DnsType::A,
+        class: DnsClass::Internet,
+    };
+    let query = DnsMessage::query(0x1234, question.clone())
+        .recursion_desired(true)
+        .dnssec_ok(true)
+        .build()
+        .unwrap();
+    assert_eq!(0x1234, query.header.id);
+    assert!(!query.header.is_response);
+    assert!(query.header.recursion_desired);
+    assert_eq!(vec![question], query.questions);
+    assert_eq!(1, query.header.question_count);
+    assert_eq!(1, query.header.additional_count);
+    let edns = query.edns.as_ref().unwrap();
+    assert_eq!(1232, edns.udp_payload_size);
+    assert!(edns.dnssec_ok);
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    query.write(&mut buf).unwrap();
+    assert_eq!(query, DnsMessage::read(&mut buf).unwrap());
+}
+
+#[cfg(test)]
+#[test]
+fn test_response_to() {
+    use crate::{DnsClass, DnsName, DnsType};
+    let question = DnsQuestion {
+        name: DnsName::new("a.b").unwrap(),
+        typ: DnsType::A,
+        class: DnsClass::Internet,
+    };
+    let query = DnsMessage::query(7, question.clone())
+        .recursion_desired(true)
+        .checking_disabled(true)
+        .build()
+        .unwrap();
+    let response = DnsMessage::response_to(&query)
+        .authoritative_answer(true)
+        .answer(DnsRecord::new_a("a.b", "10.0.0.1").unwrap())
+        .answers([DnsRecord::new_a("a.b", "10.0.0.2").unwrap()])
+        .name_server(DnsRecord::new_cname("c.b", "a.b").unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(7, response.header.id);
+    assert!(response.header.is_response);
+    assert!(response.header.authoritative_answer);
+    assert!(response.header.recursion_desired);
+    assert!(response.header.checking_disabled);
+    assert_eq!(vec![question], response.questions);
+    assert_eq!(
+        (1, 2, 1, 0),
+        (
+            response.header.question_count,
+            response.header.answer_count,
+            response.header.name_server_count,
+            response.header.additional_count
+        )
+    );
+    assert_eq!(
+        Err(DnsError::NoOptRecord),
+        DnsMessage::response_to(&query)
+            .response_code(DnsResponseCode::BadCookie)
+            .build()
+    );
+    let response = DnsMessage::response_to(&query)
+        .udp_payload_size(4096)
+        .edns_option(DnsEdnsOption {
+            code: 10,
+            data: vec![1, 2],
+        })
+        .response_code(DnsResponseCode::BadCookie)
+        .build()
+        .unwrap();
+    assert_eq!(DnsResponseCode::BadCookie, response.response_code());
+    let edns = response.edns.unwrap();
+    assert_eq!(4096, edns.udp_payload_size);
+    assert_eq!(
+        vec![DnsEdnsOption {
+            code: 10,
+            data: vec![1, 2],
+        }],
+        edns.options
+    );
+}
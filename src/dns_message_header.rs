@@ -1,5 +1,8 @@
-use crate::{read_u16_be, read_u8, write_u16_be, DnsError, DnsOpCode, DnsResponseCode};
-use fixed_buffer::FixedBuf;
+use crate::{read_exact, write_u16_be, DnsError, DnsOpCode, DnsResponseCode, FixedBuf};
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
 
 /// > 4.1.1. Header section format
 /// >
@@ -73,31 +76,57 @@ pub struct DnsMessageHeader {
     ///
     /// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.1>
     pub recursion_available: bool,
+    /// `AD` Authentic Data.  A validating resolver sets this when it has checked every RRset in
+    /// the answer and authority sections with DNSSEC.  It was one of the `Z` bits.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc4035#section-3.2.3>
+    pub authentic_data: bool,
+    /// `CD` Checking Disabled.  A resolver sets this in a query to ask for data that has not been
+    /// DNSSEC-validated.  It was one of the `Z` bits.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc4035#section-3.2.2>
+    pub checking_disabled: bool,
+    /// The lower 4 bits of the response code.  With EDNS, the OPT record holds the upper 8 bits.
+    /// See [`DnsMessage::response_code`](crate::DnsMessage::response_code).
     pub response_code: DnsResponseCode,
+    /// `QDCOUNT`, the number of questions.  [`DnsMessage::write`](crate::DnsMessage::write)
+    /// sets the four counts from the sections of the message, and
+    /// [`DnsMessage::read`](crate::DnsMessage::read) keeps the counts received.
     pub question_count: u16,
+    /// `ANCOUNT`, the number of answer records.
     pub answer_count: u16,
+    /// `NSCOUNT`, the number of authority records.
     pub name_server_count: u16,
+    /// `ARCOUNT`, the number of additional records, including the OPT record.
     pub additional_count: u16,
 }
 impl DnsMessageHeader {
     /// # Errors
     /// Returns an error when `buf` does not contain a valid message header.
     pub fn read<const N: usize>(buf: &mut FixedBuf<N>) -> Result<Self, DnsError> {
-        let id = read_u16_be(buf)?;
-        let b = read_u8(buf)?;
+        let bytes: [u8; 12] = read_exact(buf)?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// Parses the twelve bytes at the start of a message.
+    pub(crate) fn from_bytes(bytes: [u8; 12]) -> Self {
+        let id = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let b = bytes[2];
         let is_response = (b >> 7) == 1;
         let op_code = DnsOpCode::new((b >> 3) & 0xF);
         let authoritative_answer = ((b >> 2) & 1) == 1;
         let truncated = ((b >> 1) & 1) == 1;
         let recursion_desired = (b & 1) == 1;
-        let b = read_u8(buf)?;
+        let b = bytes[3];
         let recursion_available = (b >> 7) == 1;
-        let response_code = DnsResponseCode::new(b & 0xF);
-        let question_count = read_u16_be(buf)?;
-        let answer_count = read_u16_be(buf)?;
-        let name_server_count = read_u16_be(buf)?;
-        let additional_count = read_u16_be(buf)?;
-        Ok(Self {
+        let authentic_data = ((b >> 5) & 1) == 1;
+        let checking_disabled = ((b >> 4) & 1) == 1;
+        let response_code = DnsResponseCode::new(u16::from(b & 0xF));
+        let question_count = u16::from_be_bytes([bytes[4], bytes[5]]);
+        let answer_count = u16::from_be_bytes([bytes[6], bytes[7]]);
+        let name_server_count = u16::from_be_bytes([bytes[8], bytes[9]]);
+        let additional_count = u16::from_be_bytes([bytes[10], bytes[11]]);
+        Self {
             id,
             is_response,
             op_code,
@@ -105,12 +134,14 @@ impl DnsMessageHeader {
             truncated,
             recursion_desired,
             recursion_available,
+            authentic_data,
+            checking_disabled,
             response_code,
             question_count,
             answer_count,
             name_server_count,
             additional_count,
-        })
+        }
     }
 
     /// # Errors
@@ -126,7 +157,10 @@ impl DnsMessageHeader {
             | u8::from(self.recursion_desired);
         out.write_bytes(&[b])
             .map_err(|_| DnsError::ResponseBufferFull)?;
-        let b = (u8::from(self.recursion_available) << 7) | self.response_code.num();
+        let b = (u8::from(self.recursion_available) << 7)
+            | (u8::from(self.authentic_data) << 5)
+            | (u8::from(self.checking_disabled) << 4)
+            | self.response_code.header_bits();
         out.write_bytes(&[b])
             .map_err(|_| DnsError::ResponseBufferFull)?;
         for count in [
@@ -140,3 +174,156 @@ impl DnsMessageHeader {
         Ok(())
     }
 }
+/// Writes the header as the two lines that start dig output:
+///
+/// ```text
+/// ;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4660
+/// ;; flags: qr aa rd; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 0
+/// ```
+impl Display for DnsMessageHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(
+            f,
+            ";; ->>HEADER<<- opcode: {}, status: {}, id: {}",
+            self.op_code, self.response_code, self.id
+        )?;
+        write!(f, ";; flags:")?;
+        for (flag, set) in [
+            ("qr", self.is_response),
+            ("aa", self.authoritative_answer),
+            ("tc", self.truncated),
+            ("rd", self.recursion_desired),
+            ("ra", self.recursion_available),
+            ("ad", self.authentic_data),
+            ("cd", self.checking_disabled),
+        ] {
+            if set {
+                write!(f, " {flag}")?;
+            }
+        }
+        write!(
+            f,
+            "; QUERY: {}, ANSWER: {}, AUTHORITY: {}, ADDITIONAL: {}",
+            self.question_count, self.answer_count, self.name_server_count, self.additional_count
+        )
+    }
+}
+/// Parses the two header lines written by [`Display`].  Fields on a missing line are zero.
+impl FromStr for DnsMessageHeader {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let err = || format!("not a DNS message header: {value:?}");
+        let mut header = Self::from_bytes([0; 12]);
+        for line in value.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(fields) = line.strip_prefix(";; ->>HEADER<<-") {
+                for field in fields.split(',') {
+                    match field.trim().split_once(':').ok_or_else(err)? {
+                        ("opcode", op_code) => header.op_code = op_code.trim().parse()?,
+                        ("status", status) => header.response_code = status.trim().parse()?,
+                        ("id", id) => header.id = id.trim().parse().map_err(|_| err())?,
+                        _ => return Err(err()),
+                    }
+                }
+            } else if let Some(rest) = line.strip_prefix(";; flags:") {
+                let (flags, counts) = rest.split_once(';').ok_or_else(err)?;
+                for flag in flags.split_ascii_whitespace() {
+                    match flag {
+                        "qr" => header.is_response = true,
+                        "aa" => header.authoritative_answer = true,
+                        "tc" => header.truncated = true,
+                        "rd" => header.recursion_desired = true,
+                        "ra" => header.recursion_available = true,
+                        "ad" => header.authentic_data = true,
+                        "cd" => header.checking_disabled = true,
+                        _ => return Err(format!("unknown header flag {flag:?}: {value:?}")),
+                    }
+                }
+                for field in counts.split(',') {
+                    let (name, count) = field.trim().split_once(':').ok_or_else(err)?;
+                    let count = count.trim().parse().map_err(|_| err())?;
+                    match name {
+                        "QUERY" => header.question_count = count,
+                        "ANSWER" => header.answer_count = count,
+                        "AUTHORITY" => header.name_server_count = count,
+                        "ADDITIONAL" => header.additional_count = count,
+                        _ => return Err(err()),
+                    }
+                }
+            } else {
+                return Err(err());
+            }
+        }
+        Ok(header)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsMessageHeader {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::from_bytes(u.arbitrary()?))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    let header = DnsMessageHeader {
+        id: 0x1234,
+        is_response: true,
+        op_code: DnsOpCode::Query,
+        authoritative_answer: true,
+        truncated: false,
+        recursion_desired: true,
+        recursion_available: false,
+        authentic_data: false,
+        checking_disabled: true,
+        response_code: DnsResponseCode::NameError,
+        question_count: 1,
+        answer_count: 0,
+        name_server_count: 0,
+        additional_count: 1,
+    };
+    assert_eq!(
+        ";; ->>HEADER<<- opcode: QUERY, status: NXDOMAIN, id: 4660\n\
+        ;; flags: qr aa rd cd; QUERY: 1, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1",
+        header.to_string()
+    );
+    assert_eq!(Ok(header.clone()), header.to_string().parse());
+    assert_eq!(
+        Ok(DnsMessageHeader::from_bytes([0; 12])),
+        ";; flags:; QUERY: 0, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 0".parse()
+    );
+    ";; flags: xx; QUERY: 0"
+        .parse::<DnsMessageHeader>()
+        .unwrap_err();
+    ";; ->>HEADER<<- id: 65536"
+        .parse::<DnsMessageHeader>()
+        .unwrap_err();
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_write_flags() {
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[0x12, 0x34, 0x81, 0x33, 0, 1, 0, 0, 0, 0, 0, 0])
+        .unwrap();
+    let header = DnsMessageHeader::read(&mut buf).unwrap();
+    assert!(header.is_response);
+    assert!(header.recursion_desired);
+    assert!(!header.recursion_available);
+    assert!(header.authentic_data);
+    assert!(header.checking_disabled);
+    assert_eq!(DnsResponseCode::NameError, header.response_code);
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    header.write(&mut out).unwrap();
+    assert_eq!([0x12, 0x34, 0x81, 0x33], out.readable()[..4]);
+    // Only the lower 4 bits of the response code go in the header.
+    let header = DnsMessageHeader {
+        response_code: DnsResponseCode::BadCookie,
+        ..header
+    };
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    header.write(&mut out).unwrap();
+    assert_eq!(0x37, out.readable()[3]);
+}
@@ -0,0 +1,252 @@
+use crate::{
+    DnsEdns, DnsError, DnsMessage, DnsMessageHeader, DnsQuestionRef, DnsRecord, DnsRecordRef,
+    DnsResponseCode, DnsType,
+};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// A message borrowed in its wire form.
+///
+/// [`DnsMessageRef::new`] checks the structure of the whole message without allocating.  The
+/// section iterators then decode one entry at a time, and names stay in the message, so a server
+/// can answer a request without copying it into a [`DnsMessage`].
+#[derive(Clone, Debug)]
+pub struct DnsMessageRef<'a> {
+    message: &'a [u8],
+    header: DnsMessageHeader,
+    answers_offset: usize,
+    name_servers_offset: usize,
+    additional_offset: usize,
+    /// Offset of the OPT pseudo-record in the additional section.
+    edns_offset: Option<usize>,
+}
+impl<'a> DnsMessageRef<'a> {
+    /// Checks the structure of `message`.  It checks names and record lengths, but not RDATA.
+    ///
+    /// # Errors
+    /// Returns an error when `message` does not contain a valid message.
+    pub fn new(message: &'a [u8]) -> Result<Self, DnsError> {
+        let header_bytes = message.get(..12).ok_or(DnsError::Truncated)?;
+        let header = DnsMessageHeader::from_bytes(
+            <[u8; 12]>::try_from(header_bytes)
+                .map_err(|_| DnsError::Unreachable(file!(), line!()))?,
+        );
+        let mut offset = 12;
+        for _ in 0..header.question_count {
+            offset = DnsQuestionRef::read(message, offset)?.1;
+        }
+        let answers_offset = offset;
+        for _ in 0..header.answer_count {
+            offset = DnsRecordRef::read(message, offset)?.1;
+        }
+        let name_servers_offset = offset;
+        for _ in 0..header.name_server_count {
+            offset = DnsRecordRef::read(message, offset)?.1;
+        }
+        let additional_offset = offset;
+        let mut edns_offset = None;
+        for _ in 0..header.additional_count {
+            let (record, next) = DnsRecordRef::read(message, offset)?;
+            if record.typ() == DnsType::OPT {
+                // > If a query message with more than one OPT RR is received, a FORMERR
+                // > (RCODE=1) MUST be returned.
+                // https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.1
+                if edns_offset.is_some() {
+                    return Err(DnsError::TooManyOptRecords);
+                }
+                if record.name().labels().next().is_some() {
+                    return Err(DnsError::InvalidOptRecord);
+                }
+                Self::record_to_edns(&record)?;
+                edns_offset = Some(offset);
+            }
+            offset = next;
+        }
+        Ok(Self {
+            message,
+            header,
+            answers_offset,
+            name_servers_offset,
+            additional_offset,
+            edns_offset,
+        })
+    }
+
+    fn record_to_edns(record: &DnsRecordRef<'_>) -> Result<DnsEdns, DnsError> {
+        DnsEdns::from_fields(record.class().num(), record.raw_ttl(), record.rdata())
+    }
+
+    fn records(&self, offset: usize, count: u16) -> impl Iterator<Item = DnsRecordRef<'a>> {
+        let message = self.message;
+        let mut offset = offset;
+        // `new` checked the records, so reads succeed.
+        (0..count).filter_map(move |_| {
+            let (record, next) = DnsRecordRef::read(message, offset).ok()?;
+            offset = next;
+            Some(record)
+        })
+    }
+
+    #[must_use]
+    pub fn header(&self) -> &DnsMessageHeader {
+        &self.header
+    }
+
+    pub fn questions(&self) -> impl Iterator<Item = DnsQuestionRef<'a>> {
+        let message = self.message;
+        let mut offset = 12;
+        // `new` checked the questions, so reads succeed.
+        (0..self.header.question_count).filter_map(move |_| {
+            let (question, next) = DnsQuestionRef::read(message, offset).ok()?;
+            offset = next;
+            Some(question)
+        })
+    }
+
+    pub fn answers(&self) -> impl Iterator<Item = DnsRecordRef<'a>> {
+        self.records(self.answers_offset, self.header.answer_count)
+    }
+
+    pub fn name_servers(&self) -> impl Iterator<Item = DnsRecordRef<'a>> {
+        self.records(self.name_servers_offset, self.header.name_server_count)
+    }
+
+    /// Returns the records in the additional section, except the OPT pseudo-record.
+    pub fn additional(&self) -> impl Iterator<Item = DnsRecordRef<'a>> {
+        self.records(self.additional_offset, self.header.additional_count)
+            .filter(|record| record.typ() != DnsType::OPT)
+    }
+
+    /// Returns the OPT pseudo-record from the additional section.
+    #[must_use]
+    pub fn edns(&self) -> Option<DnsEdns> {
+        let (record, _) = DnsRecordRef::read(self.message, self.edns_offset?).ok()?;
+        Self::record_to_edns(&record).ok()
+    }
+
+    /// Returns the response code, combining the 4 bits in the header with the upper 8 bits in the
+    /// OPT record, when there is one.  See [`DnsMessage::response_code`].
+    #[must_use]
+    pub fn response_code(&self) -> DnsResponseCode {
+        DnsMessage::combine_response_code(&self.header, self.edns().as_ref())
+    }
+
+    /// Copies the message into a [`DnsMessage`].  Like [`DnsMessage::read`], this ignores invalid
+    /// additional records.
+    ///
+    /// # Errors
+    /// Returns an error when a question or record has an unsupported class or invalid RDATA.
+    pub fn to_message(&self) -> Result<DnsMessage, DnsError> {
+        Ok(DnsMessage {
+            header: self.header.clone(),
+            questions: self.questions().map(|q| q.to_question()).collect(),
+            answers: self
+                .answers()
+                .map(|record| record.to_record())
+                .collect::<Result<_, _>>()?,
+            name_servers: self
+                .name_servers()
+                .map(|record| record.to_record())
+                .collect::<Result<_, _>>()?,
+            additional: self
+                .additional()
+                .filter_map(|record| record.to_record().ok())
+                .collect(),
+            edns: self.edns(),
+        })
+    }
+
+    /// # Errors
+    /// Returns an error when there are more than 65,536 questions or answers.
+    pub fn answer_response<'x>(
+        &self,
+        answers: impl Iterator<Item = &'x DnsRecord>,
+    ) -> Result<DnsMessage, DnsError> {
+        DnsMessage::response(
+            &self.header,
+            self.questions().map(|q| q.to_question()).collect(),
+            DnsResponseCode::NoError,
+            answers.cloned().collect(),
+        )
+    }
+
+    /// # Errors
+    /// Returns an error when there are more than 65,536 questions.
+    pub fn error_response(&self, response_code: DnsResponseCode) -> Result<DnsMessage, DnsError> {
+        DnsMessage::response(
+            &self.header,
+            self.questions().map(|q| q.to_question()).collect(),
+            response_code,
+            Vec::new(),
+        )
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_new() {
+    use crate::FixedBuf;
+    use crate::{DnsName, DnsQuestion};
+    let request = DnsMessage {
+        header: DnsMessageHeader {
+            id: 0x1234,
+            is_response: false,
+            op_code: crate::DnsOpCode::Query,
+            authoritative_answer: false,
+            truncated: false,
+            recursion_desired: true,
+            recursion_available: false,
+            authentic_data: false,
+            checking_disabled: false,
+            response_code: DnsResponseCode::NoError,
+            question_count: 1,
+            answer_count: 1,
+            name_server_count: 0,
+            additional_count: 2,
+        },
+        questions: vec![DnsQuestion {
+            name: DnsName::new("b.a.com").unwrap(),
+            typ: DnsType::A,
+            class: crate::DnsClass::Internet,
+        }],
+        answers: vec![DnsRecord::new_cname("b.a.com", "c.a.com").unwrap()],
+        name_servers: Vec::new(),
+        additional: vec![DnsRecord::new_a("c.a.com", "10.0.0.1").unwrap()],
+        edns: Some(DnsEdns::new(1232)),
+    };
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    request.write(&mut buf).unwrap();
+    let message = DnsMessageRef::new(buf.readable()).unwrap();
+    assert_eq!(&request.header, message.header());
+    let questions: Vec<DnsQuestionRef<'_>> = message.questions().collect();
+    assert_eq!(1, questions.len());
+    assert!(questions[0].name.eq_name(&DnsName::new("B.A.com").unwrap()));
+    assert_eq!(1, message.answers().count());
+    assert_eq!(0, message.name_servers().count());
+    assert_eq!(
+        vec![DnsRecord::new_a("c.a.com", "10.0.0.1").unwrap()],
+        message
+            .additional()
+            .map(|record| record.to_record().unwrap())
+            .collect::<Vec<DnsRecord>>()
+    );
+    assert_eq!(Some(DnsEdns::new(1232)), message.edns());
+    assert_eq!(request, message.to_message().unwrap());
+    assert_eq!(request, DnsMessage::read(&mut buf.clone()).unwrap());
+    // Truncated.
+    let len = buf.len();
+    assert_eq!(
+        Err(DnsError::Truncated),
+        DnsMessageRef::new(&buf.readable()[..len - 1]).map(|_| ())
+    );
+    // Duplicate OPT.
+    let bytes = [
+        0x12, 0x34, 0x00, 0x00, 0, 0, 0, 0, 0, 0, 0, 2, // header
+        0, 0, 41, 2, 0, 0, 0, 0, 0, 0, 0, // OPT
+        0, 0, 41, 2, 0, 0, 0, 0, 0, 0, 0, // OPT
+    ];
+    assert_eq!(
+        Err(DnsError::TooManyOptRecords),
+        DnsMessageRef::new(&bytes).map(|_| ())
+    );
+}
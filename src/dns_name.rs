@@ -1,57 +1,27 @@
-use crate::{read_u8, DnsError};
+use crate::{read_offset, DnsError, DnsNameRef, FixedBuf};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
 use core::convert::TryFrom;
-use core::fmt::{Display, Formatter};
-use fixed_buffer::FixedBuf;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use core::str::FromStr;
+use idna::uts46::{AsciiDenyList, DnsLength, Hyphens, Uts46};
 
-/// > 2.3.1. Preferred name syntax
-/// >
-/// > The DNS specifications attempt to be as general as possible in the rules for constructing
-/// > domain names.  The idea is that the name of any existing object can be expressed as a domain
-/// > name with minimal changes.
-/// >
-/// > However, when assigning a domain name for an object, the prudent user will select a name
-/// > which satisfies both the rules of the domain system and any existing rules for the object,
-/// > whether these rules are published or implied by existing programs.
-/// >
-/// > For example, when naming a mail domain, the user should satisfy both the rules of this memo
-/// > and those in [RFC-822](https://datatracker.ietf.org/doc/html/rfc822).  When creating a new
-/// > host name, the old rules for HOSTS.TXT should be followed.  This avoids problems when old
-/// > software is converted to use domain names.
-/// >
-/// > The following syntax will result in fewer problems with many
-/// >
-/// > applications that use domain names (e.g., mail, TELNET).
-/// >
-/// > `<domain> ::= <subdomain> | " "`
-/// >
-/// > `<subdomain> ::= <label> | <subdomain> "." <label>`
-/// >
-/// > `<label> ::= <letter> [ [ <ldh-str> ] <let-dig> ]`
-/// >
-/// > `<ldh-str> ::= <let-dig-hyp> | <let-dig-hyp> <ldh-str>`
-/// >
-/// > `<let-dig-hyp> ::= <let-dig> | "-"`
-/// >
-/// > `<let-dig> ::= <letter> | <digit>`
-/// >
-/// > `<letter> ::=` any one of the 52 alphabetic characters `A` through `Z` in upper case
-/// > and `a` through `z` in lower case
-/// >
-/// > `<digit> ::=` any one of the ten digits `0` through `9`
-/// >
-/// > Note that while upper and lower case letters are allowed in domain names, no significance is
-/// > attached to the case.  That is, two names with the same spelling but different case are to be
-/// > treated as if identical.
-/// >
-/// > The labels must follow the rules for ARPANET host names.  They must start with a letter, end
-/// > with a letter or digit, and have as interior characters only letters, digits, and hyphen.
-/// > There are also some restrictions on the length.  Labels must be 63 characters or less.
-/// >
-/// > For example, the following strings identify hosts in the Internet:
-/// >
-/// > `A.ISI.EDU XX.LCS.MIT.EDU SRI-NIC.ARPA`
+/// A domain name.
 ///
-/// <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.1>
+/// > The DNS itself places only one restriction on the particular labels that can be used to
+/// > identify resource records.  That one restriction relates to the length of the label and the
+/// > full name.  The length of any one label is limited to between 1 and 63 octets.  A full domain
+/// > name is limited to 255 octets (including the separators).  The zero length full name is
+/// > defined as representing the root of the DNS tree, and is typically written and displayed as
+/// > ".".  Those restrictions aside, any binary string whatever can be used as the label of any
+/// > resource record.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc2181#section-11>
 ///
 /// > Various objects and parameters in the DNS have size limits.  They are listed below.  Some
 /// > could be easily changed, others are more fundamental.
@@ -62,91 +32,471 @@ use fixed_buffer::FixedBuf;
 /// > - UDP messages: 512 octets or less
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4>
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub struct DnsName(String);
-impl DnsName {
-    fn is_letter(b: u8) -> bool {
-        b.is_ascii_lowercase() || b.is_ascii_uppercase()
-    }
+///
+/// Names keep the case they were made with, but compare, hash, and sort ignoring ASCII case.
+///
+/// > Note that while upper and lower case letters are allowed in domain names, no significance is
+/// > attached to the case.  That is, two names with the same spelling but different case are to be
+/// > treated as if identical.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.1>
+///
+/// Resolvers that randomize the case of query names check that responses echo it, so servers
+/// must not change it.
+///
+/// > When a DNS server receives a query, it MUST, in the response, echo the question name
+/// > exactly as it was received.
+///
+/// <https://datatracker.ietf.org/doc/html/draft-vixie-dnsext-dns0x20-00#section-4>
+#[derive(Clone)]
+pub struct DnsName {
+    /// The name in wire form: each label preceded by its length, then the zero-length root label.
+    wire: Vec<u8>,
+    /// The name in presentation form, without the trailing dot.
+    text: String,
+}
 
+/// The longest name in wire form, counting the length octet of each label and the root label.
+///
+/// > To simplify implementations, the total length of a domain name (i.e., label octets and
+/// > label length octets) is restricted to 255 octets or less.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.1>
+pub(crate) const MAX_NAME_LEN: usize = 255;
+
+impl DnsName {
     fn is_letter_digit(b: u8) -> bool {
-        Self::is_letter(b) || b.is_ascii_digit()
+        b.is_ascii_alphanumeric()
     }
 
     fn is_letter_digit_hyphen(b: u8) -> bool {
         Self::is_letter_digit(b) || b == b'-'
     }
 
-    fn is_valid_label(label: &str) -> bool {
-        if label.is_empty() || label.len() > 63 {
-            return false;
+    fn is_hostname_label(label: &[u8]) -> bool {
+        match (label.first(), label.last()) {
+            (Some(first), Some(last)) => {
+                Self::is_letter_digit(*first)
+                    && label.iter().copied().all(Self::is_letter_digit_hyphen)
+                    && Self::is_letter_digit(*last)
+            }
+            _ => false,
         }
-        let bytes = label.as_bytes();
-        Self::is_letter(bytes[0])
-            && bytes.iter().copied().all(Self::is_letter_digit_hyphen)
-            && Self::is_letter_digit(*bytes.last().unwrap())
     }
 
-    fn is_valid_name(value: &str) -> bool {
-        if !value.is_ascii() {
-            return false;
+    /// Returns the length of a name with `labels` in wire form, including the root label.
+    pub(crate) fn wire_len<'x>(labels: impl Iterator<Item = &'x [u8]>) -> usize {
+        labels.map(|label| 1 + label.len()).sum::<usize>() + 1
+    }
+
+    /// Makes a name from labels already known to be 1 to 63 octets long, with a total length of
+    /// at most [`MAX_NAME_LEN`].
+    pub(crate) fn from_checked_labels<'x>(labels: impl Iterator<Item = &'x [u8]>) -> Self {
+        let mut wire = Vec::new();
+        for label in labels {
+            #[allow(clippy::cast_possible_truncation)]
+            wire.push(label.len() as u8);
+            wire.extend_from_slice(label);
+        }
+        wire.push(0);
+        Self::from_wire(wire)
+    }
+
+    /// Makes a name from its wire form, which must be valid.
+    fn from_wire(wire: Vec<u8>) -> Self {
+        let mut name = Self {
+            wire,
+            text: String::new(),
+        };
+        let mut text = String::new();
+        for (n, label) in name.labels().enumerate() {
+            if n != 0 {
+                text.push('.');
+            }
+            // Writing to a `String` does not fail.
+            let _ = Self::fmt_label(&mut text, label);
         }
-        value.split('.').all(Self::is_valid_label)
+        name.text = text;
+        name
     }
 
+    /// Parses a name in presentation form.
+    ///
+    /// Labels may contain any octet.  Write `\.` for a dot inside a label, `\\` for a backslash,
+    /// and `\DDD` for the octet with decimal value `DDD`.
+    ///
+    /// > `\X` where X is any character other than a digit (0-9), is used to quote that character
+    /// > so that its special meaning does not apply.  For example, "\\." can be used to place a
+    /// > dot character in a label.
+    /// >
+    /// > `\DDD` where each D is a digit is the octet corresponding to the decimal number described
+    /// > by DDD.  The resulting octet is assumed to be text and is not checked for special
+    /// > meaning.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-5.1>
+    ///
+    /// This accepts names that are not valid host names, like `_sip._tcp.example.com`.  Use
+    /// [`DnsName::is_hostname`] to check host name syntax.
+    ///
+    /// Names with non-ASCII characters are converted to A-labels with UTS 46 processing, so
+    /// `bücher.example` becomes `xn--bcher-kva.example`.  Use [`DnsName::to_unicode`] to get the
+    /// U-labels back.
+    ///
+    /// <https://www.unicode.org/reports/tr46/#ToASCII>
+    ///
     /// # Errors
     /// Returns an error when `value` is not a valid DNS name.
     pub fn new(value: &str) -> Result<Self, String> {
-        let trimmed = value.strip_suffix('.').unwrap_or(value);
-        if trimmed.len() > 255 || !Self::is_valid_name(trimmed) {
-            return Err(format!("not a valid DNS name: {value:?}"));
+        let err = || format!("not a valid DNS name: {value:?}");
+        let a_labels;
+        let value = if value.is_ascii() {
+            value
+        } else {
+            a_labels = Uts46::new()
+                .to_ascii(
+                    value.as_bytes(),
+                    AsciiDenyList::EMPTY,
+                    Hyphens::Allow,
+                    DnsLength::Ignore,
+                )
+                .map_err(|_| err())?;
+            &a_labels
+        };
+        let mut labels: Vec<Vec<u8>> = vec![Vec::new()];
+        let mut bytes = value.bytes();
+        while let Some(b) = bytes.next() {
+            if b == b'.' {
+                labels.push(Vec::new());
+                continue;
+            }
+            let label = labels.last_mut().ok_or_else(err)?;
+            match b {
+                b'\\' => match bytes.next() {
+                    Some(d) if d.is_ascii_digit() => {
+                        let mut octet = u16::from(d - b'0');
+                        for _ in 0..2 {
+                            match bytes.next() {
+                                Some(d) if d.is_ascii_digit() => {
+                                    octet = octet * 10 + u16::from(d - b'0');
+                                }
+                                _ => return Err(err()),
+                            }
+                        }
+                        label.push(u8::try_from(octet).map_err(|_| err())?);
+                    }
+                    Some(c) if c.is_ascii_graphic() => label.push(c),
+                    _ => return Err(err()),
+                },
+                // Spaces and control characters must be escaped.
+                b if b.is_ascii_graphic() => label.push(b),
+                _ => return Err(err()),
+            }
+        }
+        // A trailing dot makes the name absolute.
+        if labels.len() > 1 && labels.last().is_some_and(Vec::is_empty) {
+            labels.pop();
+        }
+        if labels
+            .iter()
+            .any(|label| label.is_empty() || label.len() > 63)
+            || Self::wire_len(labels.iter().map(Vec::as_slice)) > MAX_NAME_LEN
+        {
+            return Err(err());
+        }
+        Ok(Self::from_checked_labels(labels.iter().map(Vec::as_slice)))
+    }
+
+    /// Returns `true` when every label follows the host name syntax.
+    ///
+    /// > 2.3.1. Preferred name syntax
+    /// >
+    /// > The DNS specifications attempt to be as general as possible in the rules for constructing
+    /// > domain names.  The idea is that the name of any existing object can be expressed as a domain
+    /// > name with minimal changes.
+    /// >
+    /// > However, when assigning a domain name for an object, the prudent user will select a name
+    /// > which satisfies both the rules of the domain system and any existing rules for the object,
+    /// > whether these rules are published or implied by existing programs.
+    /// >
+    /// > For example, when naming a mail domain, the user should satisfy both the rules of this memo
+    /// > and those in [RFC-822](https://datatracker.ietf.org/doc/html/rfc822).  When creating a new
+    /// > host name, the old rules for HOSTS.TXT should be followed.  This avoids problems when old
+    /// > software is converted to use domain names.
+    /// >
+    /// > The following syntax will result in fewer problems with many
+    /// >
+    /// > applications that use domain names (e.g., mail, TELNET).
+    /// >
+    /// > `<domain> ::= <subdomain> | " "`
+    /// >
+    /// > `<subdomain> ::= <label> | <subdomain> "." <label>`
+    /// >
+    /// > `<label> ::= <letter> [ [ <ldh-str> ] <let-dig> ]`
+    /// >
+    /// > `<ldh-str> ::= <let-dig-hyp> | <let-dig-hyp> <ldh-str>`
+    /// >
+    /// > `<let-dig-hyp> ::= <let-dig> | "-"`
+    /// >
+    /// > `<let-dig> ::= <letter> | <digit>`
+    /// >
+    /// > `<letter> ::=` any one of the 52 alphabetic characters `A` through `Z` in upper case
+    /// > and `a` through `z` in lower case
+    /// >
+    /// > `<digit> ::=` any one of the ten digits `0` through `9`
+    /// >
+    /// > Note that while upper and lower case letters are allowed in domain names, no significance is
+    /// > attached to the case.  That is, two names with the same spelling but different case are to be
+    /// > treated as if identical.
+    /// >
+    /// > The labels must follow the rules for ARPANET host names.  They must start with a letter, end
+    /// > with a letter or digit, and have as interior characters only letters, digits, and hyphen.
+    /// > There are also some restrictions on the length.  Labels must be 63 characters or less.
+    /// >
+    /// > For example, the following strings identify hosts in the Internet:
+    /// >
+    /// > `A.ISI.EDU XX.LCS.MIT.EDU SRI-NIC.ARPA`
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.1>
+    ///
+    /// > The syntax of a legal Internet host name was specified in RFC-952 \[DNS:4\].  One aspect
+    /// > of host name syntax is hereby changed: the restriction on the first character is relaxed
+    /// > to allow either a letter or a digit.  Host software MUST support this more liberal
+    /// > syntax.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1123#section-2.1>
+    #[must_use]
+    pub fn is_hostname(&self) -> bool {
+        !self.is_root() && self.labels().all(Self::is_hostname_label)
+    }
+
+    /// Makes the root name, which has no labels.
+    #[must_use]
+    pub fn root() -> Self {
+        Self::from_wire(vec![0])
+    }
+
+    /// Returns `true` for the root name, which has no labels.
+    #[must_use]
+    pub fn is_root(&self) -> bool {
+        self.wire.len() == 1
+    }
+
+    /// Returns the labels of the name, starting with the leftmost one.  The root name has no
+    /// labels.
+    pub fn labels(&self) -> impl Iterator<Item = &[u8]> {
+        let mut rest = &self.wire[..];
+        core::iter::from_fn(move || {
+            let (len, tail) = rest.split_first()?;
+            let label = tail
+                .get(..usize::from(*len))
+                .filter(|label| !label.is_empty())?;
+            rest = &tail[label.len()..];
+            Some(label)
+        })
+    }
+
+    /// Returns the number of labels, not counting the root label.
+    #[must_use]
+    pub fn label_count(&self) -> usize {
+        self.labels().count()
+    }
+
+    /// Returns the name without its leftmost label, or `None` for the root name.
+    #[must_use]
+    pub fn parent(&self) -> Option<Self> {
+        if self.is_root() {
+            return None;
         }
-        Ok(Self(trimmed.to_ascii_lowercase()))
+        let rest = 1 + usize::from(self.wire[0]);
+        Some(Self::from_wire(self.wire[rest..].to_vec()))
     }
 
+    /// Returns `true` when this name is `other` or is below it.
+    ///
+    /// > A domain is a subdomain of another domain if it is contained within that domain.  This
+    /// > relationship can be tested by seeing if the subdomain's name ends with the containing
+    /// > domain's name.  For example, A.B.C.D is a subdomain of B.C.D, C.D, D, and " ".
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1034#section-3.1>
+    #[must_use]
+    pub fn is_subdomain_of(&self, other: &DnsName) -> bool {
+        let Some(start) = self.wire.len().checked_sub(other.wire.len()) else {
+            return false;
+        };
+        // The suffix must start at a label, not in the middle of one.
+        let mut offset = 0;
+        while offset < start {
+            offset += 1 + usize::from(self.wire[offset]);
+        }
+        offset == start && self.wire[start..].eq_ignore_ascii_case(&other.wire)
+    }
+
+    /// Returns the name with `label` added on the left.
+    ///
     /// # Errors
-    /// Returns an error when `buf` does not contain a valid name.
-    pub fn read<const N: usize>(buf: &mut FixedBuf<N>) -> Result<DnsName, DnsError> {
-        let mut value = String::new();
-        for _ in 0..63 {
-            let len = read_u8(buf)? as usize;
-            if len == 0 {
-                if value.len() > 255 {
-                    return Err(DnsError::NameTooLong);
-                }
-                return Ok(Self(value));
-            }
-            if buf.readable().len() < len {
-                return Err(DnsError::Truncated);
+    /// Returns an error when `label` is empty or longer than 63 octets, or the result is too
+    /// long.
+    pub fn prepend_label(&self, label: &[u8]) -> Result<Self, DnsError> {
+        if label.is_empty() || label.len() > 63 {
+            return Err(DnsError::InvalidLabel);
+        }
+        let labels = || core::iter::once(label).chain(self.labels());
+        if Self::wire_len(labels()) > MAX_NAME_LEN {
+            return Err(DnsError::NameTooLong);
+        }
+        Ok(Self::from_checked_labels(labels()))
+    }
+
+    /// Returns the name with the labels of `suffix` added on the right.
+    ///
+    /// # Errors
+    /// Returns an error when the result is too long.
+    pub fn append(&self, suffix: &DnsName) -> Result<Self, DnsError> {
+        let labels = || self.labels().chain(suffix.labels());
+        if Self::wire_len(labels()) > MAX_NAME_LEN {
+            return Err(DnsError::NameTooLong);
+        }
+        Ok(Self::from_checked_labels(labels()))
+    }
+
+    /// Returns the name that holds PTR records for `addr`, like `1.0.0.10.in-addr.arpa` for
+    /// `10.0.0.1`.
+    ///
+    /// > Domain names in the IN-ADDR.ARPA domain are defined to include up to four labels in
+    /// > addition to the IN-ADDR.ARPA suffix.  Each label represents one octet of an Internet
+    /// > address, and is expressed as a character string for a decimal value in the range 0-255
+    /// > (with leading zeros omitted except in the case of a zero octet which is represented by
+    /// > a single zero).
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.5>
+    ///
+    /// > An IPv6 address is represented as a name in the IP6.ARPA domain by a sequence of nibbles
+    /// > separated by dots with the suffix ".IP6.ARPA".  The sequence of nibbles is encoded in
+    /// > reverse order, i.e., the low-order nibble is encoded first, followed by the next
+    /// > low-order nibble and so on.  Each nibble is represented by a hexadecimal digit.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc3596#section-2.5>
+    #[must_use]
+    pub fn reverse(addr: IpAddr) -> Self {
+        let (mut labels, suffix): (Vec<Vec<u8>>, [&[u8]; 2]) = match addr {
+            IpAddr::V4(addr) => (
+                addr.octets()
+                    .iter()
+                    .rev()
+                    .map(|octet| octet.to_string().into_bytes())
+                    .collect(),
+                [b"in-addr", b"arpa"],
+            ),
+            IpAddr::V6(addr) => (
+                addr.octets()
+                    .iter()
+                    .rev()
+                    .flat_map(|octet| [octet & 0xF, octet >> 4])
+                    .map(|nibble| format!("{nibble:x}").into_bytes())
+                    .collect(),
+                [b"ip6", b"arpa"],
+            ),
+        };
+        labels.extend(suffix.iter().map(|label| label.to_vec()));
+        Self::from_checked_labels(labels.iter().map(Vec::as_slice))
+    }
+
+    /// Returns the address whose PTR records this name holds, or `None` when the name is not an
+    /// address in `in-addr.arpa` or `ip6.arpa`.  This undoes [`DnsName::reverse`].
+    #[must_use]
+    pub fn reverse_addr(&self) -> Option<IpAddr> {
+        let labels: Vec<&[u8]> = self.labels().collect();
+        match labels.as_slice() {
+            [d, c, b, a, in_addr, arpa]
+                if in_addr.eq_ignore_ascii_case(b"in-addr")
+                    && arpa.eq_ignore_ascii_case(b"arpa") =>
+            {
+                let octet = |label: &[u8]| -> Option<u8> {
+                    if !label.iter().all(u8::is_ascii_digit)
+                        || (label.len() > 1 && label[0] == b'0')
+                    {
+                        return None;
+                    }
+                    core::str::from_utf8(label).ok()?.parse().ok()
+                };
+                Some(IpAddr::V4(Ipv4Addr::new(
+                    octet(a)?,
+                    octet(b)?,
+                    octet(c)?,
+                    octet(d)?,
+                )))
             }
-            let label_bytes = buf.read_bytes(len);
-            let label = std::str::from_utf8(label_bytes).map_err(|_| DnsError::InvalidLabel)?;
-            if !Self::is_valid_label(label) {
-                return Err(DnsError::InvalidLabel);
+            [nibbles @ .., ip6, arpa]
+                if nibbles.len() == 32
+                    && ip6.eq_ignore_ascii_case(b"ip6")
+                    && arpa.eq_ignore_ascii_case(b"arpa") =>
+            {
+                let mut octets = [0_u8; 16];
+                for (n, label) in nibbles.iter().enumerate() {
+                    let [digit] = label else {
+                        return None;
+                    };
+                    let nibble = u8::try_from(char::from(*digit).to_digit(16)?).ok()?;
+                    // The first label is the low nibble of the last octet.
+                    octets[15 - n / 2] |= if n.is_multiple_of(2) {
+                        nibble
+                    } else {
+                        nibble << 4
+                    };
+                }
+                Some(IpAddr::V6(Ipv6Addr::from(octets)))
             }
-            if !value.is_empty() {
-                value.push('.');
+            _ => None,
+        }
+    }
+
+    /// Returns the name in wire form, ending with the root label.
+    pub(crate) fn wire(&self) -> &[u8] {
+        &self.wire
+    }
+
+    /// Writes `label` in presentation form, escaping special characters.
+    pub(crate) fn fmt_label(f: &mut impl core::fmt::Write, label: &[u8]) -> core::fmt::Result {
+        for b in label {
+            match b {
+                b'.' | b'\\' | b'"' | b'(' | b')' | b';' | b'@' | b'$' => {
+                    write!(f, "\\{}", char::from(*b))?;
+                }
+                b if b.is_ascii_graphic() => write!(f, "{}", char::from(*b))?,
+                b => write!(f, "\\{b:03}")?,
             }
-            value.push_str(label);
         }
-        Err(DnsError::TooManyLabels)
+        Ok(())
+    }
+
+    /// Reads a name, following any compression pointers.
+    ///
+    /// > In order to reduce the size of messages, the domain system utilizes a compression scheme
+    /// > which eliminates the repetition of domain names in a message.  In this scheme, an entire
+    /// > domain name or a list of labels at the end of a domain name is replaced with a pointer to
+    /// > a prior occurance of the same name.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4>
+    ///
+    /// Pointer offsets are resolved against the memory of `buf`, so the message must start at the
+    /// beginning of the buffer.  Every pointer must point to an offset before the previous one,
+    /// which rules out loops.
+    ///
+    /// # Errors
+    /// Returns an error when `buf` does not contain a valid name.
+    pub fn read<const N: usize>(buf: &mut FixedBuf<N>) -> Result<DnsName, DnsError> {
+        let start = read_offset(buf);
+        let message = &buf.mem()[..start + buf.len()];
+        let (name, end) = DnsNameRef::read(message, start)?;
+        let name = name.to_name();
+        buf.read_bytes(end - start);
+        Ok(name)
     }
 
     /// # Errors
     /// Returns an error when `buf` fills up.
     pub fn write<const N: usize>(&self, out: &mut FixedBuf<N>) -> Result<(), DnsError> {
-        for label in self.0.split('.') {
-            if label.len() > 63 {
-                return Err(DnsError::Unreachable(file!(), line!()));
-            }
-            let len =
-                u8::try_from(label.len()).map_err(|_| DnsError::Unreachable(file!(), line!()))?;
-            out.write_bytes(&[len])
-                .map_err(|_| DnsError::ResponseBufferFull)?;
-            out.write_bytes(label.as_bytes())
-                .map_err(|_| DnsError::ResponseBufferFull)?;
-        }
-        out.write_bytes(&[0])
+        out.write_bytes(&self.wire)
             .map_err(|_| DnsError::ResponseBufferFull)?;
         Ok(())
     }
@@ -159,17 +509,110 @@ impl DnsName {
         Ok(buf)
     }
 
+    /// Returns the name in presentation form, without the trailing dot.
     #[must_use]
     pub fn inner(&self) -> &str {
-        &self.0
+        &self.text
+    }
+
+    /// Returns the name in presentation form with A-labels converted to U-labels, for display.
+    /// Labels that are not valid A-labels stay as they are.
+    ///
+    /// > An "A-label" is the ASCII-Compatible Encoding (ACE, see Section 2.3.2.5) form of an
+    /// > IDNA-valid string.  It must be a complete label: IDNA is defined for labels, not for
+    /// > parts of them and not for complete domain names.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc5890#section-2.3.2.1>
+    #[must_use]
+    pub fn to_unicode(&self) -> String {
+        let mut result = String::new();
+        for (n, label) in self.labels().enumerate() {
+            if n != 0 {
+                result.push('.');
+            }
+            if label.len() > 4 && label[..4].eq_ignore_ascii_case(b"xn--") {
+                let (u_label, valid) =
+                    Uts46::new().to_unicode(label, AsciiDenyList::EMPTY, Hyphens::Allow);
+                if valid.is_ok() {
+                    result.push_str(&u_label);
+                    continue;
+                }
+            }
+            // Writing to a String cannot fail.
+            let _ = Self::fmt_label(&mut result, label);
+        }
+        result
+    }
+}
+impl PartialEq for DnsName {
+    fn eq(&self, other: &Self) -> bool {
+        self.wire.eq_ignore_ascii_case(&other.wire)
+    }
+}
+impl Eq for DnsName {}
+impl Hash for DnsName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for b in &self.wire {
+            state.write_u8(b.to_ascii_lowercase());
+        }
+    }
+}
+impl PartialOrd for DnsName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// Sorts names in DNSSEC canonical order.
+///
+/// > For the purposes of DNS security, owner names are ordered by treating individual labels as
+/// > unsigned left-justified octet strings.  The absence of a octet sorts before a zero value
+/// > octet, and uppercase US-ASCII letters are treated as if they were lowercase US-ASCII
+/// > letters.
+/// >
+/// > To compute the canonical ordering of a set of DNS names, start by sorting the names
+/// > according to their most significant (rightmost) labels.  For names in which the most
+/// > significant label is identical, continue sorting according to their next most significant
+/// > label, and so forth.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc4034#section-6.1>
+impl Ord for DnsName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lower = |b: &u8| b.to_ascii_lowercase();
+        let labels: Vec<&[u8]> = self.labels().collect();
+        let other_labels: Vec<&[u8]> = other.labels().collect();
+        for (label, other_label) in labels.iter().rev().zip(other_labels.iter().rev()) {
+            match label.iter().map(lower).cmp(other_label.iter().map(lower)) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+        labels.len().cmp(&other_labels.len())
+    }
+}
+impl Debug for DnsName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "DnsName({:?})", self.to_string())
     }
 }
 impl Display for DnsName {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.text)
+    }
+}
+/// Parses a name in presentation form, like [`DnsName::new`], but also accepts `.` for the root
+/// name.
+impl FromStr for DnsName {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "." {
+            Ok(Self::root())
+        } else {
+            Self::new(value)
+        }
     }
 }
-impl std::convert::TryFrom<&'static str> for DnsName {
+impl core::convert::TryFrom<&'static str> for DnsName {
     type Error = String;
 
     fn try_from(value: &'static str) -> Result<Self, Self::Error> {
@@ -177,12 +620,29 @@ impl std::convert::TryFrom<&'static str> for DnsName {
     }
 }
 
+/// Makes valid names, with labels of any octets.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsName {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut labels: Vec<&[u8]> = Vec::new();
+        while u.arbitrary()? {
+            let len = u.int_in_range(1..=63)?;
+            labels.push(u.bytes(len)?);
+            if Self::wire_len(labels.iter().copied()) > MAX_NAME_LEN {
+                labels.pop();
+                break;
+            }
+        }
+        Ok(Self::from_checked_labels(labels.into_iter()))
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_err() {
     assert_eq!(
-        <Result<DnsName, String>>::Err("not a valid DNS name: \"abc!\"".to_string()),
-        DnsName::new("abc!")
+        <Result<DnsName, String>>::Err("not a valid DNS name: \"abc def\"".to_string()),
+        DnsName::new("abc def")
     );
 }
 
@@ -200,15 +660,13 @@ fn test_new_label_separators() {
 #[cfg(test)]
 #[test]
 fn test_new_label_charset() {
-    const ALLOWED: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-.";
-    for c in ALLOWED.chars() {
-        let value = format!("a{c}a");
-        DnsName::new(&value).expect(&value);
-    }
-    for b in 0..=255_u8 {
+    // Backslash starts an escape.  See `test_new_escapes`.
+    for b in (0..=127_u8).filter(|b| *b != b'\\') {
         let c = char::from(b);
-        if !ALLOWED.contains(c) {
-            let value = format!("a{c}a");
+        let value = format!("a{c}a");
+        if c.is_ascii_graphic() {
+            DnsName::new(&value).expect(&value);
+        } else {
             assert_eq!(
                 <Result<DnsName, String>>::Err(format!("not a valid DNS name: {value:?}")),
                 DnsName::new(&value)
@@ -216,46 +674,148 @@ fn test_new_label_charset() {
         }
     }
     assert_eq!(
-        <Result<DnsName, String>>::Err("not a valid DNS name: \"a\u{263A}\"".to_string()),
-        DnsName::new("a\u{263A}")
+        <Result<DnsName, String>>::Err(format!("not a valid DNS name: {:?}", "\u{301}a")),
+        DnsName::new("\u{301}a")
+    );
+    assert_eq!("_sip._tcp", DnsName::new("_sip._tcp").unwrap().inner());
+    assert_eq!(
+        "1password.com",
+        DnsName::new("1password.com").unwrap().inner()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_new_escapes() {
+    let name = DnsName::new("a\\.b.c").unwrap();
+    assert_eq!(
+        vec![&b"a.b"[..], &b"c"[..]],
+        name.labels().collect::<Vec<&[u8]>>()
+    );
+    assert_eq!("a\\.b.c", name.inner());
+    let name = DnsName::new("\\000\\032\\255\\\\x").unwrap();
+    assert_eq!(
+        vec![&[0_u8, 32, 255, b'\\', b'x'][..]],
+        name.labels().collect::<Vec<&[u8]>>()
+    );
+    assert_eq!("\\000\\032\\255\\\\x", name.inner());
+    assert_eq!("a\\(b\\)", DnsName::new("a(b)").unwrap().inner());
+    assert_eq!("a.b", DnsName::new("\\a.b").unwrap().inner());
+    DnsName::new("a\\").unwrap_err();
+    DnsName::new("a\\25").unwrap_err();
+    DnsName::new("a\\256").unwrap_err();
+    DnsName::new("a\\ b").unwrap_err();
+    // Escaped octets count once toward the label length.
+    DnsName::new(&"\\000".repeat(63)).unwrap();
+    DnsName::new(&"\\000".repeat(64)).unwrap_err();
+}
+
+#[cfg(test)]
+#[test]
+fn test_new_unicode() {
+    assert_eq!(
+        "xn--bcher-kva.example",
+        DnsName::new("bücher.example").unwrap().inner()
+    );
+    assert_eq!(
+        "xn--bcher-kva.example",
+        DnsName::new("BÜCHER.Example.").unwrap().inner()
+    );
+    // IDNA 2008 keeps the sharp s.
+    assert_eq!("xn--fa-hia.de", DnsName::new("faß.de").unwrap().inner());
+    assert_eq!(
+        "xn--r8jz45g.xn--zckzah",
+        DnsName::new("例え。テスト").unwrap().inner()
+    );
+    assert_eq!("xn--a-60p", DnsName::new("a\u{263A}").unwrap().inner());
+    assert_eq!(
+        "_dmarc.xn--bcher-kva",
+        DnsName::new("_dmarc.bücher").unwrap().inner()
     );
+    DnsName::new("bücher..example").unwrap_err();
+    DnsName::new("bü cher").unwrap_err();
+    DnsName::new(&format!("{}ü", "a".repeat(63))).unwrap_err();
 }
 
 #[cfg(test)]
 #[test]
-fn test_new_label_normalizing() {
+fn test_to_unicode() {
+    assert_eq!(
+        "bücher.example",
+        DnsName::new("bücher.example").unwrap().to_unicode()
+    );
+    assert_eq!(
+        "例え.テスト",
+        DnsName::new("xn--r8jz45g.xn--zckzah").unwrap().to_unicode()
+    );
+    assert_eq!(
+        "xn--zz.a\\.b",
+        DnsName::new("xn--zz.a\\.b").unwrap().to_unicode()
+    );
+    assert_eq!("", DnsName::read(&mut buf_with(&[0])).unwrap().to_unicode());
+}
+
+#[cfg(test)]
+#[test]
+fn test_new_keeps_case() {
     assert_eq!(
         "abcdefghijklmnopqrstuvwxyz",
         DnsName::new("abcdefghijklmnopqrstuvwxyz").unwrap().inner()
     );
     assert_eq!(
-        "abcdefghijklmnopqrstuvwxyz",
+        "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
         DnsName::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ").unwrap().inner()
     );
     assert_eq!("a0123456789", DnsName::new("a0123456789").unwrap().inner());
     assert_eq!("a-b.c", DnsName::new("a-b.c").unwrap().inner());
     assert_eq!(
-        "xyz321-654abc.def",
+        "Xyz321-654abC.DeF",
         DnsName::new("Xyz321-654abC.DeF").unwrap().inner()
     );
 }
 
 #[cfg(test)]
 #[test]
-fn test_new_label_format() {
-    DnsName::new("a").unwrap();
-    DnsName::new("1").unwrap_err();
-    DnsName::new("1a").unwrap_err();
-    DnsName::new("a1").unwrap();
-    DnsName::new("a9876543210").unwrap();
-    DnsName::new("-").unwrap_err();
-    DnsName::new("a-").unwrap_err();
-    DnsName::new("-a").unwrap_err();
-    DnsName::new("a-.b").unwrap_err();
-    DnsName::new("a.-b").unwrap_err();
-    DnsName::new("a-b").unwrap();
-    DnsName::new("a-0").unwrap();
-    DnsName::new("a---b").unwrap();
+fn test_compare_ignores_case() {
+    use std::collections::hash_map::DefaultHasher;
+    let hash = |name: &DnsName| {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        hasher.finish()
+    };
+    let lower = DnsName::new("xyz321-654abc.def").unwrap();
+    let mixed = DnsName::new("Xyz321-654abC.DeF").unwrap();
+    assert_eq!(lower, mixed);
+    assert_eq!(hash(&lower), hash(&mixed));
+    assert_eq!(Ordering::Equal, lower.cmp(&mixed));
+    assert_ne!(lower, DnsName::new("xyz321-654abc.de").unwrap());
+    // Only ASCII letters fold.
+    assert_ne!(
+        DnsName::new("\\193").unwrap(),
+        DnsName::new("\\225").unwrap()
+    );
+    assert!(DnsName::new("A").unwrap() < DnsName::new("b").unwrap());
+}
+
+#[cfg(test)]
+#[test]
+fn test_is_hostname() {
+    let is_hostname = |value: &str| DnsName::new(value).unwrap().is_hostname();
+    assert!(is_hostname("a"));
+    assert!(is_hostname("1"));
+    assert!(is_hostname("1a"));
+    assert!(is_hostname("a1"));
+    assert!(is_hostname("a9876543210"));
+    assert!(!is_hostname("-"));
+    assert!(!is_hostname("a-"));
+    assert!(!is_hostname("-a"));
+    assert!(!is_hostname("a-.b"));
+    assert!(!is_hostname("a.-b"));
+    assert!(is_hostname("a-b"));
+    assert!(is_hostname("a-0"));
+    assert!(is_hostname("a---b"));
+    assert!(!is_hostname("_sip._tcp.a"));
+    assert!(!is_hostname("a\\.b"));
 }
 
 #[cfg(test)]
@@ -274,16 +834,38 @@ fn test_new_name_length() {
         "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
         "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
         "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
-        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
     ))
     .unwrap();
     DnsName::new(concat!(
         "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
         "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
         "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
-        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa."
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa."
     ))
     .unwrap();
+    // 256 octets in wire form, though only 254 in presentation form.
+    DnsName::new(concat!(
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+    ))
+    .unwrap_err();
+    DnsName::new(concat!(
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+    ))
+    .unwrap_err();
+    DnsName::new(concat!(
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa."
+    ))
+    .unwrap_err();
     DnsName::new(concat!(
         "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
         "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.",
@@ -297,7 +879,7 @@ fn test_new_name_length() {
         "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
         "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
         "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
-        "a.a.a",
+        "a.a",
     ))
     .unwrap();
     DnsName::new(concat!(
@@ -306,13 +888,270 @@ fn test_new_name_length() {
         "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
         "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
         "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
-        "a.a.aa",
+        "a.a.a",
+    ))
+    .unwrap_err();
+    DnsName::new(concat!(
+        "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
+        "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
+        "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
+        "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
+        "a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.a.",
+        "a.aa",
     ))
     .unwrap_err();
 }
 
-// TODO: Test read()
-// TODO: Test write()
+#[cfg(test)]
+#[test]
+fn test_labels() {
+    let name = DnsName::new("a.Bc.d").unwrap();
+    assert_eq!(
+        vec![&b"a"[..], &b"Bc"[..], &b"d"[..]],
+        name.labels().collect::<Vec<&[u8]>>()
+    );
+    assert_eq!(3, name.label_count());
+    assert_eq!(0, DnsName::root().label_count());
+    assert!(DnsName::root().is_root());
+    assert!(!name.is_root());
+}
+
+#[cfg(test)]
+#[test]
+fn test_parent() {
+    let name = DnsName::new("a.b.c").unwrap();
+    let parent = name.parent().unwrap();
+    assert_eq!("b.c", parent.inner());
+    let grandparent = parent.parent().unwrap();
+    assert_eq!("c", grandparent.inner());
+    let root = grandparent.parent().unwrap();
+    assert!(root.is_root());
+    assert_eq!(None, root.parent());
+}
+
+#[cfg(test)]
+#[test]
+fn test_is_subdomain_of() {
+    let name = DnsName::new("a.b.C.d").unwrap();
+    assert!(name.is_subdomain_of(&name));
+    assert!(name.is_subdomain_of(&DnsName::new("b.c.d").unwrap()));
+    assert!(name.is_subdomain_of(&DnsName::new("c.D").unwrap()));
+    assert!(name.is_subdomain_of(&DnsName::new("d").unwrap()));
+    assert!(name.is_subdomain_of(&DnsName::root()));
+    assert!(!name.is_subdomain_of(&DnsName::new("x.a.b.c.d").unwrap()));
+    assert!(!name.is_subdomain_of(&DnsName::new("e").unwrap()));
+    // Suffixes must start at a label.
+    assert!(!DnsName::new("ab.c")
+        .unwrap()
+        .is_subdomain_of(&DnsName::new("b.c").unwrap()));
+    assert!(!DnsName::root().is_subdomain_of(&DnsName::new("a").unwrap()));
+}
+
+#[cfg(test)]
+#[test]
+fn test_prepend_append() {
+    let name = DnsName::new("b.c").unwrap();
+    assert_eq!("a.b.c", name.prepend_label(b"a").unwrap().inner());
+    assert_eq!("a\\.x.b.c", name.prepend_label(b"a.x").unwrap().inner());
+    assert_eq!(Err(DnsError::InvalidLabel), name.prepend_label(b""));
+    assert_eq!(Err(DnsError::InvalidLabel), name.prepend_label(&[b'a'; 64]));
+    assert_eq!(
+        "x.y.b.c",
+        DnsName::new("x.y").unwrap().append(&name).unwrap().inner()
+    );
+    assert_eq!("b.c", DnsName::root().append(&name).unwrap().inner());
+    let long = DnsName::new(&["a".repeat(63), "a".repeat(63), "a".repeat(63)].join(".")).unwrap();
+    assert_eq!(
+        63 * 3 + 61 + 3,
+        long.append(&DnsName::new(&"a".repeat(61)).unwrap())
+            .unwrap()
+            .inner()
+            .len()
+    );
+    assert_eq!(Err(DnsError::NameTooLong), long.append(&long));
+    assert_eq!(
+        Err(DnsError::NameTooLong),
+        long.prepend_label(&[b'a'; 61]).unwrap().prepend_label(b"a")
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_canonical_order() {
+    // https://datatracker.ietf.org/doc/html/rfc4034#section-6.1
+    let expected: Vec<DnsName> = [
+        "example",
+        "a.example",
+        "yljkjljk.a.example",
+        "Z.a.example",
+        "zABC.a.EXAMPLE",
+        "z.example",
+        "\\001.z.example",
+        "*.z.example",
+        "\\200.z.example",
+    ]
+    .iter()
+    .map(|value| DnsName::new(value).unwrap())
+    .collect();
+    let mut names = expected.clone();
+    names.reverse();
+    names.sort();
+    assert_eq!(expected, names);
+    assert!(DnsName::root() < DnsName::new("a").unwrap());
+}
+
+#[cfg(test)]
+fn buf_with(bytes: &[u8]) -> FixedBuf<512> {
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(bytes).unwrap();
+    buf
+}
+
+#[cfg(test)]
+#[test]
+fn test_read() {
+    let mut buf = buf_with(&[1, b'a', 3, b'c', b'o', b'm', 0, 7]);
+    assert_eq!("a.com", DnsName::read(&mut buf).unwrap().inner());
+    assert_eq!([7], buf.readable());
+    assert_eq!("", DnsName::read(&mut buf_with(&[0])).unwrap().inner());
+    assert_eq!(
+        Err(DnsError::Truncated),
+        DnsName::read(&mut buf_with(&[1, b'a']))
+    );
+    assert_eq!(
+        Err(DnsError::Truncated),
+        DnsName::read(&mut buf_with(&[3, b'a']))
+    );
+    assert_eq!(
+        "!.\\255",
+        DnsName::read(&mut buf_with(&[1, b'!', 1, 255, 0]))
+            .unwrap()
+            .inner()
+    );
+    // Reserved label types.
+    assert_eq!(
+        Err(DnsError::InvalidLabel),
+        DnsName::read(&mut buf_with(&[0x40, 0]))
+    );
+    assert_eq!(
+        Err(DnsError::InvalidLabel),
+        DnsName::read(&mut buf_with(&[0x80, 0]))
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_pointers() {
+    let mut buf = buf_with(&[
+        1, b'a', 3, b'c', b'o', b'm', 0, // 0: a.com
+        1, b'b', 0xC0, 2, // 7: b.com
+        1, b'c', 0xC0, 7, // 11: c.b.com
+        0xC0, 11, 9, // 15: c.b.com
+    ]);
+    assert_eq!("a.com", DnsName::read(&mut buf).unwrap().inner());
+    assert_eq!("b.com", DnsName::read(&mut buf).unwrap().inner());
+    assert_eq!("c.b.com", DnsName::read(&mut buf).unwrap().inner());
+    assert_eq!("c.b.com", DnsName::read(&mut buf).unwrap().inner());
+    assert_eq!([9], buf.readable());
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_bad_pointers() {
+    // Truncated pointer.
+    assert_eq!(
+        Err(DnsError::Truncated),
+        DnsName::read(&mut buf_with(&[1, b'a', 0xC0]))
+    );
+    // Pointer to itself.
+    assert_eq!(
+        Err(DnsError::InvalidPointer),
+        DnsName::read(&mut buf_with(&[0xC0, 0]))
+    );
+    // Forward pointer.
+    assert_eq!(
+        Err(DnsError::InvalidPointer),
+        DnsName::read(&mut buf_with(&[0xC0, 2, 1, b'a', 0]))
+    );
+    // Loop through an earlier name.
+    let mut buf = buf_with(&[1, b'a', 0xC0, 4, 1, b'b', 0xC0, 0]);
+    buf.read_bytes(4);
+    assert_eq!(Err(DnsError::InvalidPointer), DnsName::read(&mut buf));
+    // Pointer past the end of the message.
+    let mut buf = buf_with(&[0, 0xC0, 0x3F]);
+    buf.read_bytes(1);
+    assert_eq!(Err(DnsError::InvalidPointer), DnsName::read(&mut buf));
+    // Too many hops.  Each pointer points to the one before it.
+    let mut bytes = vec![1, b'a', 0, 0xC0, 0];
+    for n in 0..crate::dns_name_ref::MAX_POINTER_HOPS {
+        bytes.extend([0xC0, u8::try_from(3 + n * 2).unwrap()]);
+    }
+    let mut buf = buf_with(&bytes);
+    buf.read_bytes(bytes.len() - 2);
+    assert_eq!(Err(DnsError::TooManyPointers), DnsName::read(&mut buf));
+    let mut buf = buf_with(&bytes);
+    buf.read_bytes(bytes.len() - 4);
+    assert_eq!("a", DnsName::read(&mut buf).unwrap().inner());
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_name_too_long() {
+    // 255 octets
+    let mut bytes = Vec::new();
+    for _ in 0..3 {
+        bytes.push(63);
+        bytes.extend([b'a'; 63]);
+    }
+    bytes.push(61);
+    bytes.extend([b'a'; 61]);
+    bytes.push(0);
+    assert_eq!(
+        255,
+        DnsName::read(&mut buf_with(&bytes)).unwrap().wire().len()
+    );
+    // 257 octets, though only 255 in presentation form
+    let mut bytes = Vec::new();
+    for _ in 0..4 {
+        bytes.push(63);
+        bytes.extend([b'a'; 63]);
+    }
+    bytes.push(0);
+    assert_eq!(
+        Err(DnsError::NameTooLong),
+        DnsName::read(&mut buf_with(&bytes))
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_write() {
+    let mut out: FixedBuf<256> = FixedBuf::new();
+    DnsName::new("a.Bc").unwrap().write(&mut out).unwrap();
+    assert_eq!([1, b'a', 2, b'B', b'c', 0], out.readable());
+}
+
+#[cfg(all(test, feature = "arbitrary"))]
+#[test]
+fn test_arbitrary_write_read() {
+    use arbitrary::{Arbitrary, Unstructured};
+    let mut seed = 1_u32;
+    for _ in 0..1000 {
+        let bytes: Vec<u8> = (0..300)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                seed.to_be_bytes()[1]
+            })
+            .collect();
+        let Ok(name) = DnsName::arbitrary(&mut Unstructured::new(&bytes)) else {
+            continue;
+        };
+        let mut buf: FixedBuf<512> = FixedBuf::new();
+        name.write(&mut buf).unwrap();
+        let read = DnsName::read(&mut buf).unwrap();
+        assert_eq!(name.wire(), read.wire());
+    }
+}
 
 #[cfg(test)]
 #[test]
@@ -330,3 +1169,48 @@ fn test_display() {
 }
 
 // TODO: Test TryFrom
+
+#[cfg(test)]
+#[test]
+fn test_from_str() {
+    assert_eq!(Ok(DnsName::root()), ".".parse());
+    assert_eq!(Ok(DnsName::new("a.b").unwrap()), "a.b.".parse());
+    assert_eq!(Ok(DnsName::new("a.b").unwrap()), "a.b".parse());
+    "".parse::<DnsName>().unwrap_err();
+    "a..b".parse::<DnsName>().unwrap_err();
+}
+
+#[cfg(test)]
+#[test]
+fn test_reverse() {
+    let addr: IpAddr = "10.0.0.1".parse().unwrap();
+    let name = DnsName::reverse(addr);
+    assert_eq!("1.0.0.10.in-addr.arpa", name.inner());
+    assert_eq!(Some(addr), name.reverse_addr());
+    let addr: IpAddr = "2001:db8::567:89ab".parse().unwrap();
+    let name = DnsName::reverse(addr);
+    assert_eq!(
+        "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+        name.inner()
+    );
+    assert_eq!(Some(addr), name.reverse_addr());
+    assert_eq!(
+        Some(addr),
+        DnsName::new("B.A.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.B.D.0.1.0.0.2.IP6.ARPA")
+            .unwrap()
+            .reverse_addr()
+    );
+    for name in [
+        "0.0.10.in-addr.arpa",
+        "256.0.0.10.in-addr.arpa",
+        "01.0.0.10.in-addr.arpa",
+        "+1.0.0.10.in-addr.arpa",
+        "1.0.0.10.in-addr.arpa.com",
+        "a.b.c.d.in-addr.arpa",
+        "0.1.0.0.2.ip6.arpa",
+        "bb.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+        "example.com",
+    ] {
+        assert_eq!(None, DnsName::new(name).unwrap().reverse_addr(), "{name}");
+    }
+}
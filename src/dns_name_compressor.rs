@@ -0,0 +1,165 @@
+use crate::{write_bytes, write_u16_be, DnsError, DnsName, FixedBuf};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Remembers where names were written in a message, so later names can point to them.
+///
+/// > In order to reduce the size of messages, the domain system utilizes a compression scheme
+/// > which eliminates the repetition of domain names in a message.  In this scheme, an entire
+/// > domain name or a list of labels at the end of a domain name is replaced with a pointer to
+/// > a prior occurance of the same name.
+/// >
+/// > The pointer takes the form of a two octet sequence:
+/// >
+/// > ```text
+/// >     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// >     | 1  1|                OFFSET                   |
+/// >     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// > ```
+/// >
+/// > The first two bits are ones.  This allows a pointer to be distinguished from a label, since
+/// > the label must begin with two zero bits because labels are restricted to 63 octets or less.
+/// > The OFFSET field specifies an offset from the start of the message (i.e., the first octet of
+/// > the ID field in the domain header).
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4>
+#[derive(Clone, Debug)]
+pub(crate) struct DnsNameCompressor {
+    /// Length of the output buffer when the message started.
+    start: usize,
+    /// Message offsets of name suffixes already written.  `None` disables compression.
+    suffixes: Option<BTreeMap<Vec<u8>, u16>>,
+}
+impl DnsNameCompressor {
+    /// Makes a compressor for a message that starts at the current end of `out`.
+    pub fn new<const N: usize>(out: &FixedBuf<N>) -> Self {
+        Self {
+            start: out.len(),
+            suffixes: Some(BTreeMap::new()),
+        }
+    }
+
+    /// Makes a compressor that writes every name in full.
+    pub fn disabled() -> Self {
+        Self {
+            start: 0,
+            suffixes: None,
+        }
+    }
+
+    /// Returns the message offset of the next byte written to `out`.
+    pub fn offset<const N: usize>(&self, out: &FixedBuf<N>) -> usize {
+        out.len() - self.start
+    }
+
+    /// # Errors
+    /// Returns an error when `out` fills up.
+    pub fn write<const N: usize>(
+        &mut self,
+        name: &DnsName,
+        out: &mut FixedBuf<N>,
+    ) -> Result<(), DnsError> {
+        let offset = self.offset(out);
+        self.write_at(name, offset, out)
+    }
+
+    /// Writes `name` to `out`, which is a scratch buffer whose first byte will end up at message
+    /// offset `offset`.  Use this for names in RDATA, which must be written before RDLENGTH is
+    /// known.
+    ///
+    /// # Errors
+    /// Returns an error when `out` fills up.
+    pub fn write_at<const N: usize>(
+        &mut self,
+        name: &DnsName,
+        offset: usize,
+        out: &mut FixedBuf<N>,
+    ) -> Result<(), DnsError> {
+        let Some(suffixes) = &mut self.suffixes else {
+            return name.write(out);
+        };
+        let mut offset = offset;
+        // Each suffix is a run of labels ending with the root label.
+        let mut rest = name.wire();
+        while rest.len() > 1 {
+            if let Some(target) = suffixes.get(rest) {
+                return write_u16_be(out, 0xC000 | target);
+            }
+            // Pointers have only 14 bits for the offset.
+            if let Ok(target) = u16::try_from(offset) {
+                if target < 0x4000 {
+                    suffixes.insert(rest.to_vec(), target);
+                }
+            }
+            let label_len = 1 + usize::from(rest[0]);
+            write_bytes(out, &rest[..label_len])?;
+            offset += label_len;
+            rest = &rest[label_len..];
+        }
+        write_bytes(out, &[0])
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_write() {
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    out.write_bytes(&[7, 7]).unwrap();
+    let mut compressor = DnsNameCompressor::new(&out);
+    compressor
+        .write(&DnsName::new("a.b").unwrap(), &mut out)
+        .unwrap();
+    compressor
+        .write(&DnsName::new("c.a.b").unwrap(), &mut out)
+        .unwrap();
+    compressor
+        .write(&DnsName::new("b").unwrap(), &mut out)
+        .unwrap();
+    compressor
+        .write(&DnsName::new("c.a.b").unwrap(), &mut out)
+        .unwrap();
+    compressor
+        .write(&DnsName::new("d").unwrap(), &mut out)
+        .unwrap();
+    assert_eq!(
+        [
+            7, 7, // not part of the message
+            1, b'a', 1, b'b', 0, // 0: a.b
+            1, b'c', 0xC0, 0, // 5: c.a.b
+            0xC0, 2, // b
+            0xC0, 5, // c.a.b
+            1, b'd', 0, // d
+        ],
+        out.readable()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_write_at() {
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    let mut compressor = DnsNameCompressor::new(&out);
+    let mut scratch: FixedBuf<512> = FixedBuf::new();
+    compressor
+        .write_at(&DnsName::new("a.b").unwrap(), 20, &mut scratch)
+        .unwrap();
+    compressor
+        .write(&DnsName::new("b").unwrap(), &mut out)
+        .unwrap();
+    assert_eq!([0xC0, 22], out.readable());
+}
+
+#[cfg(test)]
+#[test]
+fn test_disabled() {
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    let mut compressor = DnsNameCompressor::disabled();
+    compressor
+        .write(&DnsName::new("a").unwrap(), &mut out)
+        .unwrap();
+    compressor
+        .write(&DnsName::new("a").unwrap(), &mut out)
+        .unwrap();
+    assert_eq!([1, b'a', 0, 1, b'a', 0], out.readable());
+}
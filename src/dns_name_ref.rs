@@ -0,0 +1,179 @@
+use crate::dns_name::MAX_NAME_LEN;
+use crate::{DnsError, DnsName};
+use core::fmt::{Display, Formatter};
+
+/// The maximum number of compression pointers followed while reading one name.
+pub(crate) const MAX_POINTER_HOPS: usize = 32;
+
+/// A name inside a message, borrowed in its wire form.
+///
+/// The name may end in a compression pointer to an earlier part of the message.  Reading it does
+/// not allocate, and neither does comparing it with [`DnsNameRef::eq_name`].
+#[derive(Clone, Copy, Debug)]
+pub struct DnsNameRef<'a> {
+    message: &'a [u8],
+    offset: usize,
+}
+impl<'a> DnsNameRef<'a> {
+    /// Checks the name at `offset` in `message`, following any compression pointers.  Returns the
+    /// name and the offset of the byte after it.
+    ///
+    /// > In order to reduce the size of messages, the domain system utilizes a compression scheme
+    /// > which eliminates the repetition of domain names in a message.  In this scheme, an entire
+    /// > domain name or a list of labels at the end of a domain name is replaced with a pointer to
+    /// > a prior occurance of the same name.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4>
+    ///
+    /// `message` must start at the first byte of the message, since pointers are offsets from
+    /// there.  Every pointer must point to an offset before the previous one, which rules out
+    /// loops.
+    ///
+    /// # Errors
+    /// Returns an error when `message` does not contain a valid name at `offset`.
+    pub fn read(message: &'a [u8], offset: usize) -> Result<(Self, usize), DnsError> {
+        let start = offset;
+        let mut offset = offset;
+        let mut end: Option<usize> = None;
+        let mut pointer_limit = start;
+        let mut hops = 0;
+        // Length of the name in wire form, counting the root label.
+        let mut name_len = 1;
+        loop {
+            let len = *message.get(offset).ok_or(DnsError::Truncated)?;
+            match len & 0xC0 {
+                0x00 if len == 0 => {
+                    offset += 1;
+                    break;
+                }
+                0x00 => {
+                    let label = message
+                        .get(offset + 1..offset + 1 + usize::from(len))
+                        .ok_or(DnsError::Truncated)?;
+                    name_len += 1 + label.len();
+                    if name_len > MAX_NAME_LEN {
+                        return Err(DnsError::NameTooLong);
+                    }
+                    offset += 1 + usize::from(len);
+                }
+                0xC0 => {
+                    let low = *message.get(offset + 1).ok_or(DnsError::Truncated)?;
+                    let target = (usize::from(len & 0x3F) << 8) | usize::from(low);
+                    if end.is_none() {
+                        end = Some(offset + 2);
+                        pointer_limit = offset;
+                    }
+                    if target >= pointer_limit {
+                        return Err(DnsError::InvalidPointer);
+                    }
+                    hops += 1;
+                    if hops > MAX_POINTER_HOPS {
+                        return Err(DnsError::TooManyPointers);
+                    }
+                    pointer_limit = target;
+                    offset = target;
+                }
+                // 0x40 and 0x80 are reserved label types.
+                _ => return Err(DnsError::InvalidLabel),
+            }
+        }
+        Ok((
+            Self {
+                message,
+                offset: start,
+            },
+            end.unwrap_or(offset),
+        ))
+    }
+
+    /// Returns the labels of the name, following compression pointers.  The root name has no
+    /// labels.
+    pub fn labels(&self) -> impl Iterator<Item = &'a [u8]> {
+        let message = self.message;
+        let mut offset = self.offset;
+        // `read` checked the name, so these lookups succeed.
+        core::iter::from_fn(move || loop {
+            let len = *message.get(offset)?;
+            match len & 0xC0 {
+                0x00 if len == 0 => return None,
+                0x00 => {
+                    let label = message.get(offset + 1..offset + 1 + usize::from(len))?;
+                    offset += 1 + usize::from(len);
+                    return Some(label);
+                }
+                _ => {
+                    let low = *message.get(offset + 1)?;
+                    offset = (usize::from(len & 0x3F) << 8) | usize::from(low);
+                }
+            }
+        })
+    }
+
+    /// Returns `true` when this is the same name as `name`, ignoring ASCII case.
+    #[must_use]
+    pub fn eq_name(&self, name: &DnsName) -> bool {
+        let mut labels = self.labels();
+        for expected in name.labels() {
+            match labels.next() {
+                Some(label) if label.eq_ignore_ascii_case(expected) => {}
+                _ => return false,
+            }
+        }
+        labels.next().is_none()
+    }
+
+    /// Copies the name into a [`DnsName`].
+    #[must_use]
+    pub fn to_name(&self) -> DnsName {
+        // `read` checked the label and name lengths.
+        DnsName::from_checked_labels(self.labels())
+    }
+}
+impl Display for DnsNameRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        for (n, label) in self.labels().enumerate() {
+            if n != 0 {
+                write!(f, ".")?;
+            }
+            DnsName::fmt_label(f, label)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_read() {
+    let message = [7, 1, b'A', 3, b'c', b'o', b'm', 0, 1, b'b', 0xC0, 1, 9];
+    let (name, end) = DnsNameRef::read(&message, 1).unwrap();
+    assert_eq!(8, end);
+    assert_eq!("A.com", name.to_string());
+    let (name, end) = DnsNameRef::read(&message, 8).unwrap();
+    assert_eq!(12, end);
+    assert_eq!(
+        vec![&b"b"[..], &b"A"[..], &b"com"[..]],
+        name.labels().collect::<Vec<&[u8]>>()
+    );
+    assert_eq!("b.A.com", name.to_name().inner());
+    assert_eq!(
+        Err(DnsError::InvalidPointer),
+        DnsNameRef::read(&[0xC0, 0], 0).map(|(name, end)| (name.to_name(), end))
+    );
+    let (root, end) = DnsNameRef::read(&[0], 0).unwrap();
+    assert_eq!(1, end);
+    assert_eq!(0, root.labels().count());
+}
+
+#[cfg(test)]
+#[test]
+fn test_eq_name() {
+    let message = [1, b'A', 3, b'c', b'o', b'm', 0, 1, b'b', 0xC0, 0];
+    let (name, _) = DnsNameRef::read(&message, 7).unwrap();
+    assert!(name.eq_name(&DnsName::new("b.a.com").unwrap()));
+    assert!(name.eq_name(&DnsName::new("B.A.COM").unwrap()));
+    assert!(!name.eq_name(&DnsName::new("a.com").unwrap()));
+    assert!(!name.eq_name(&DnsName::new("c.b.a.com").unwrap()));
+    assert!(!name.eq_name(&DnsName::new("b.a.co").unwrap()));
+    let (root, _) = DnsNameRef::read(&[0], 0).unwrap();
+    assert!(!root.eq_name(&DnsName::new("a").unwrap()));
+}
@@ -1,3 +1,8 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
 /// > `OPCODE`  A four bit field that specifies kind of query in this message.
 /// >         This value is set by the originator of a query and copied into
 /// >         the response.  The values are:
@@ -35,3 +40,50 @@ impl DnsOpCode {
         }
     }
 }
+impl Display for DnsOpCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            DnsOpCode::Query => write!(f, "QUERY"),
+            DnsOpCode::InverseQuery => write!(f, "IQUERY"),
+            DnsOpCode::Status => write!(f, "STATUS"),
+            DnsOpCode::Reserved(n) => write!(f, "RESERVED{n}"),
+        }
+    }
+}
+impl FromStr for DnsOpCode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let upper = value.to_ascii_uppercase();
+        match upper.as_str() {
+            "QUERY" => Ok(DnsOpCode::Query),
+            "IQUERY" => Ok(DnsOpCode::InverseQuery),
+            "STATUS" => Ok(DnsOpCode::Status),
+            other => other
+                .strip_prefix("RESERVED")
+                .and_then(|n| n.parse::<u8>().ok())
+                .filter(|n| *n < 16)
+                .map(DnsOpCode::new)
+                .ok_or_else(|| format!("not a DNS opcode: {value:?}")),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsOpCode {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // The header has four bits for the opcode.
+        Ok(Self::new(u.arbitrary::<u8>()? & 0xF))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    for op_code in [DnsOpCode::Query, DnsOpCode::Status, DnsOpCode::Reserved(3)] {
+        assert_eq!(Ok(op_code), op_code.to_string().parse());
+    }
+    assert_eq!("IQUERY", DnsOpCode::InverseQuery.to_string());
+    assert_eq!(Ok(DnsOpCode::Query), "query".parse());
+    "RESERVED16".parse::<DnsOpCode>().unwrap_err();
+}
@@ -0,0 +1,60 @@
+use crate::{DnsError, DnsSection};
+use core::fmt::{Display, Formatter};
+
+/// Says which part of a message is invalid and why.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DnsParseError {
+    pub section: DnsSection,
+    /// The position of the question or record in its section, starting at 0.  Always 0 for the
+    /// header.
+    pub index: usize,
+    /// The offset of the first byte of the header, question, or record from the start of the
+    /// message.
+    pub offset: usize,
+    pub cause: DnsError,
+}
+impl Display for DnsParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self.section {
+            DnsSection::Header => write!(f, "header")?,
+            DnsSection::Question => write!(f, "question {}", self.index)?,
+            section => write!(f, "{section} record {}", self.index)?,
+        }
+        write!(f, " at byte {}: {:?}", self.offset, self.cause)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_display() {
+    assert_eq!(
+        "header at byte 0: Truncated",
+        DnsParseError {
+            section: DnsSection::Header,
+            index: 0,
+            offset: 0,
+            cause: DnsError::Truncated,
+        }
+        .to_string()
+    );
+    assert_eq!(
+        "question 1 at byte 29: InvalidLabel",
+        DnsParseError {
+            section: DnsSection::Question,
+            index: 1,
+            offset: 29,
+            cause: DnsError::InvalidLabel,
+        }
+        .to_string()
+    );
+    assert_eq!(
+        "additional record 2 at byte 100: InvalidRdata",
+        DnsParseError {
+            section: DnsSection::Additional,
+            index: 2,
+            offset: 100,
+            cause: DnsError::InvalidRdata,
+        }
+        .to_string()
+    );
+}
@@ -1,6 +1,11 @@
 use crate::dns_class::DnsClass;
-use crate::{DnsError, DnsName, DnsType};
-use fixed_buffer::FixedBuf;
+use crate::dns_name_compressor::DnsNameCompressor;
+use crate::{DnsError, DnsName, DnsType, FixedBuf};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
 
 /// > The question section is used to carry the "question" in most queries, i.e., the parameters
 /// > that define what is being asked.  The section contains QDCOUNT (usually 1) entries, each of
@@ -41,9 +46,79 @@ impl DnsQuestion {
     /// # Errors
     /// Returns an error when `buf` fills up.
     pub fn write<const N: usize>(&self, out: &mut FixedBuf<N>) -> Result<(), DnsError> {
-        self.name.write(out)?;
+        self.write_compressed(out, &mut DnsNameCompressor::disabled())
+    }
+
+    /// # Errors
+    /// Returns an error when `buf` fills up.
+    pub(crate) fn write_compressed<const N: usize>(
+        &self,
+        out: &mut FixedBuf<N>,
+        compressor: &mut DnsNameCompressor,
+    ) -> Result<(), DnsError> {
+        compressor.write(&self.name, out)?;
         self.typ.write(out)?;
         self.class.write(out)?;
         Ok(())
     }
 }
+/// Writes the question like dig does, but without the leading `;`.
+impl Display for DnsQuestion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}.\t{}\t{}", self.name, self.class, self.typ)
+    }
+}
+/// Parses a question like `;a.b. IN A`.  The leading `;` and the class are optional.
+impl FromStr for DnsQuestion {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = value
+            .strip_prefix(';')
+            .unwrap_or(value)
+            .split_ascii_whitespace()
+            .collect();
+        let (name, class, typ) = match words[..] {
+            [name, typ] => (name, "IN", typ),
+            [name, class, typ] => (name, class, typ),
+            _ => return Err(format!("not a DNS question: {value:?}")),
+        };
+        Ok(DnsQuestion {
+            name: name.parse()?,
+            typ: typ.parse()?,
+            class: class.parse()?,
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsQuestion {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            name: u.arbitrary()?,
+            typ: u.arbitrary()?,
+            // Reading rejects other classes.
+            class: if u.arbitrary()? {
+                DnsClass::Internet
+            } else {
+                DnsClass::Any
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    let question = DnsQuestion {
+        name: DnsName::new("a.b").unwrap(),
+        typ: DnsType::AAAA,
+        class: DnsClass::Internet,
+    };
+    assert_eq!("a.b.\tIN\tAAAA", question.to_string());
+    assert_eq!(Ok(question.clone()), question.to_string().parse());
+    assert_eq!(Ok(question.clone()), ";a.b. IN AAAA".parse());
+    assert_eq!(Ok(question), "a.b AAAA".parse());
+    "a.b".parse::<DnsQuestion>().unwrap_err();
+    "a.b IN AAAA x".parse::<DnsQuestion>().unwrap_err();
+}
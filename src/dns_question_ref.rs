@@ -0,0 +1,36 @@
+use crate::{DnsClass, DnsError, DnsNameRef, DnsQuestion, DnsType};
+
+/// A question inside a message, borrowed in its wire form.  See [`DnsQuestion`].
+#[derive(Clone, Debug)]
+pub struct DnsQuestionRef<'a> {
+    pub name: DnsNameRef<'a>,
+    pub typ: DnsType,
+    pub class: DnsClass,
+}
+impl<'a> DnsQuestionRef<'a> {
+    /// Reads the question at `offset` in `message`.  Returns the question and the offset of the
+    /// byte after it.
+    ///
+    /// # Errors
+    /// Returns an error when `message` does not contain a valid question at `offset`.
+    pub fn read(message: &'a [u8], offset: usize) -> Result<(Self, usize), DnsError> {
+        let (name, offset) = DnsNameRef::read(message, offset)?;
+        let fixed = message.get(offset..offset + 4).ok_or(DnsError::Truncated)?;
+        let typ = DnsType::new(u16::from_be_bytes([fixed[0], fixed[1]]));
+        let class = DnsClass::new(u16::from_be_bytes([fixed[2], fixed[3]]));
+        if class != DnsClass::Internet && class != DnsClass::Any {
+            return Err(DnsError::InvalidClass);
+        }
+        Ok((Self { name, typ, class }, offset + 4))
+    }
+
+    /// Copies the question into a [`DnsQuestion`].
+    #[must_use]
+    pub fn to_question(&self) -> DnsQuestion {
+        DnsQuestion {
+            name: self.name.to_name(),
+            typ: self.typ.clone(),
+            class: self.class,
+        }
+    }
+}
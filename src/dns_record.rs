@@ -1,11 +1,14 @@
+use crate::dns_name_compressor::DnsNameCompressor;
 use crate::{
-    read_exact, read_u16_be, read_u32_be, write_bytes, write_u16_be, write_u32_be, DnsClass,
-    DnsError, DnsName, DnsType,
+    read_u16_be, read_u32_be, write_bytes, write_u16_be, write_u32_be, DnsClass, DnsError, DnsName,
+    DnsRecordData, DnsType, FixedBuf,
 };
-use core::fmt::{Debug, Formatter};
-use fixed_buffer::FixedBuf;
-use std::convert::TryFrom;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use alloc::format;
+use alloc::string::String;
+use core::convert::TryFrom;
+use core::fmt::{Debug, Display, Formatter};
+use core::net::IpAddr;
+use core::str::FromStr;
 
 /// > 4.1.3. Resource record format
 /// >
@@ -52,26 +55,24 @@ use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.3>
 #[derive(Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub enum DnsRecord {
-    A(DnsName, std::net::Ipv4Addr),
-    AAAA(DnsName, std::net::Ipv6Addr),
-    CNAME(DnsName, DnsName),
-    Unknown(DnsName, DnsType),
+pub struct DnsRecord {
+    name: DnsName,
+    class: DnsClass,
+    ttl_seconds: u32,
+    data: DnsRecordData,
 }
 impl DnsRecord {
-    /// # Errors
-    /// Returns an error when `buf` does not contain a valid resource record.
-    pub fn read_rdata<const N: usize>(buf: &mut FixedBuf<N>) -> Result<FixedBuf<65535>, DnsError> {
-        let len = read_u16_be(buf)?;
-        if buf.len() < (len as usize) {
-            return Err(DnsError::Truncated);
+    /// The TTL of records made with the `new_*` constructors.
+    pub const DEFAULT_TTL_SECONDS: u32 = 300;
+
+    #[must_use]
+    pub fn new(name: DnsName, class: DnsClass, ttl_seconds: u32, data: DnsRecordData) -> Self {
+        Self {
+            name,
+            class,
+            ttl_seconds,
+            data,
         }
-        let borrowed_rdata = buf.read_bytes(len as usize);
-        let mut rdata: FixedBuf<65535> = FixedBuf::new();
-        rdata
-            .write_bytes(borrowed_rdata)
-            .map_err(|_| DnsError::Unreachable(file!(), line!()))?;
-        Ok(rdata)
     }
 
     /// # Errors
@@ -87,6 +88,10 @@ impl DnsRecord {
         Ok(())
     }
 
+    fn new_internet(name: DnsName, data: DnsRecordData) -> Self {
+        Self::new(name, DnsClass::Internet, Self::DEFAULT_TTL_SECONDS, data)
+    }
+
     /// # Errors
     /// Returns an error when `name` is not a valid DNS name
     /// or `ipv4_addr` is not a valid IPv4 address.
@@ -96,7 +101,7 @@ impl DnsRecord {
             .parse()
             .map_err(|e| format!("failed parsing {ipv4_addr:?} as an IP address: {e}"))?;
         match ip_addr {
-            IpAddr::V4(addr) => Ok(Self::A(dns_name, addr)),
+            IpAddr::V4(addr) => Ok(Self::new_internet(dns_name, DnsRecordData::A(addr))),
             IpAddr::V6(addr) => Err(format!(
                 "cannot create an A record with ipv6 address {addr:?}"
             )),
@@ -115,7 +120,7 @@ impl DnsRecord {
             IpAddr::V4(addr) => Err(format!(
                 "cannot create an AAAA record with ipv4 address {addr:?}"
             )),
-            IpAddr::V6(addr) => Ok(Self::AAAA(dns_name, addr)),
+            IpAddr::V6(addr) => Ok(Self::new_internet(dns_name, DnsRecordData::AAAA(addr))),
         }
     }
 
@@ -124,27 +129,171 @@ impl DnsRecord {
     pub fn new_cname(name: &str, target: &str) -> Result<Self, String> {
         let dns_name = DnsName::new(name)?;
         let dns_name_target = DnsName::new(target)?;
-        Ok(Self::CNAME(dns_name, dns_name_target))
+        Ok(Self::new_internet(
+            dns_name,
+            DnsRecordData::CNAME(dns_name_target),
+        ))
+    }
+
+    /// # Errors
+    /// Returns an error when `name` or `exchange` are not both valid DNS names.
+    pub fn new_mx(name: &str, preference: u16, exchange: &str) -> Result<Self, String> {
+        let dns_name = DnsName::new(name)?;
+        Ok(Self::new_internet(
+            dns_name,
+            DnsRecordData::MX {
+                preference,
+                exchange: DnsName::new(exchange)?,
+            },
+        ))
+    }
+
+    /// # Errors
+    /// Returns an error when `name` or `name_server` are not both valid DNS names.
+    pub fn new_ns(name: &str, name_server: &str) -> Result<Self, String> {
+        let dns_name = DnsName::new(name)?;
+        let dns_name_server = DnsName::new(name_server)?;
+        Ok(Self::new_internet(
+            dns_name,
+            DnsRecordData::NS(dns_name_server),
+        ))
+    }
+
+    /// # Errors
+    /// Returns an error when `name` or `target` are not both valid DNS names.
+    pub fn new_ptr(name: &str, target: &str) -> Result<Self, String> {
+        let dns_name = DnsName::new(name)?;
+        let dns_name_target = DnsName::new(target)?;
+        Ok(Self::new_internet(
+            dns_name,
+            DnsRecordData::PTR(dns_name_target),
+        ))
+    }
+
+    /// Makes an SRV record for the service at `name`, like `_sip._tcp.example.com`.  See
+    /// [`DnsRecordData::SRV`].
+    ///
+    /// # Errors
+    /// Returns an error when `name` or `target` are not both valid DNS names.
+    pub fn new_srv(
+        name: &str,
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: &str,
+    ) -> Result<Self, String> {
+        let dns_name = DnsName::new(name)?;
+        Ok(Self::new_internet(
+            dns_name,
+            DnsRecordData::SRV {
+                priority,
+                weight,
+                port,
+                target: DnsName::new(target)?,
+            },
+        ))
+    }
+
+    /// Makes a TXT record holding `text`, split into strings of at most 255 octets.
+    ///
+    /// # Errors
+    /// Returns an error when `name` is not a valid DNS name.
+    pub fn new_txt(name: &str, text: &str) -> Result<Self, String> {
+        let dns_name = DnsName::new(name)?;
+        Ok(Self::new_internet(
+            dns_name,
+            DnsRecordData::txt(text.as_bytes()),
+        ))
+    }
+
+    /// Makes the SOA record of the zone at `name`.  `mname` is the primary name server, and
+    /// `rname` is the mailbox of the person responsible for the zone, with the `@` written as a
+    /// dot.  See [`DnsRecordData::SOA`].
+    ///
+    /// # Errors
+    /// Returns an error when `name`, `mname`, or `rname` is not a valid DNS name.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_soa(
+        name: &str,
+        mname: &str,
+        rname: &str,
+        serial: u32,
+        refresh: u32,
+        retry: u32,
+        expire: u32,
+        minimum: u32,
+    ) -> Result<Self, String> {
+        let dns_name = DnsName::new(name)?;
+        Ok(Self::new_internet(
+            dns_name,
+            DnsRecordData::SOA {
+                mname: DnsName::new(mname)?,
+                rname: DnsName::new(rname)?,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            },
+        ))
+    }
+
+    /// Makes a record from RDATA in the RFC 3597 generic format, like `\# 4 0A000001`.
+    /// See [`DnsRecordData::parse_generic`].
+    ///
+    /// # Errors
+    /// Returns an error when `name` is not a valid DNS name or `rdata` is not valid generic
+    /// RDATA for `typ`.
+    pub fn new_generic(name: &str, typ: DnsType, rdata: &str) -> Result<Self, String> {
+        let dns_name = DnsName::new(name)?;
+        let data = DnsRecordData::parse_generic(&typ, rdata)?;
+        Ok(Self::new_internet(dns_name, data))
+    }
+
+    /// Returns the record with its TTL set to `ttl_seconds`.
+    #[must_use]
+    pub fn with_ttl(mut self, ttl_seconds: u32) -> Self {
+        self.ttl_seconds = ttl_seconds;
+        self
+    }
+
+    /// Returns the record with its owner name set to `name`.
+    #[must_use]
+    pub fn with_name(mut self, name: DnsName) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Returns the record with its class set to `class`.
+    #[must_use]
+    pub fn with_class(mut self, class: DnsClass) -> Self {
+        self.class = class;
+        self
     }
 
     #[must_use]
     pub fn name(&self) -> &DnsName {
-        match self {
-            DnsRecord::A(dns_name, _)
-            | DnsRecord::AAAA(dns_name, _)
-            | DnsRecord::CNAME(dns_name, _)
-            | DnsRecord::Unknown(dns_name, _) => dns_name,
-        }
+        &self.name
     }
 
     #[must_use]
     pub fn typ(&self) -> DnsType {
-        match self {
-            DnsRecord::A(_, _) => DnsType::A,
-            DnsRecord::AAAA(_, _) => DnsType::AAAA,
-            DnsRecord::CNAME(_, _) => DnsType::CNAME,
-            DnsRecord::Unknown(_, typ) => DnsType::Unknown(typ.num()),
-        }
+        self.data.typ()
+    }
+
+    #[must_use]
+    pub fn class(&self) -> DnsClass {
+        self.class
+    }
+
+    #[must_use]
+    pub fn ttl_seconds(&self) -> u32 {
+        self.ttl_seconds
+    }
+
+    #[must_use]
+    pub fn data(&self) -> &DnsRecordData {
+        &self.data
     }
 
     /// # Errors
@@ -156,101 +305,549 @@ impl DnsRecord {
         if class != DnsClass::Internet && class != DnsClass::Any {
             return Err(DnsError::InvalidClass);
         }
-        let _ttl_seconds = read_u32_be(buf)?;
-        let mut rdata = Self::read_rdata(buf)?;
-        match typ {
-            DnsType::A => {
-                let octets: [u8; 4] = read_exact(&mut rdata)?;
-                Ok(DnsRecord::A(name, Ipv4Addr::from(octets)))
-            }
-            DnsType::AAAA => {
-                let octets: [u8; 16] = read_exact(&mut rdata)?;
-                Ok(DnsRecord::AAAA(name, Ipv6Addr::from(octets)))
-            }
-            DnsType::CNAME => Ok(DnsRecord::CNAME(name, DnsName::read(&mut rdata)?)),
-            DnsType::MX
-            | DnsType::NS
-            | DnsType::PTR
-            | DnsType::SOA
-            | DnsType::TXT
-            | DnsType::ANY
-            | DnsType::Unknown(_) => Ok(DnsRecord::Unknown(name, typ)),
-        }
+        // > Implementations should treat TTL values received with the most significant bit set as
+        // > if the entire value received was zero.
+        // https://datatracker.ietf.org/doc/html/rfc2181#section-8
+        let ttl_seconds = match read_u32_be(buf)? {
+            ttl if ttl > 0x7FFF_FFFF => 0,
+            ttl => ttl,
+        };
+        let rdata_len = usize::from(read_u16_be(buf)?);
+        let data = DnsRecordData::read(&typ, rdata_len, buf)?;
+        Ok(Self::new(name, class, ttl_seconds, data))
     }
 
     /// # Errors
     /// Returns an error when `buf` is full.
     pub fn write<const N: usize>(&self, out: &mut FixedBuf<N>) -> Result<(), DnsError> {
-        self.name().write(out)?;
+        self.write_compressed(out, &mut DnsNameCompressor::disabled())
+    }
+
+    /// # Errors
+    /// Returns an error when `buf` is full.
+    pub(crate) fn write_compressed<const N: usize>(
+        &self,
+        out: &mut FixedBuf<N>,
+        compressor: &mut DnsNameCompressor,
+    ) -> Result<(), DnsError> {
+        compressor.write(self.name(), out)?;
         self.typ().write(out)?;
-        DnsClass::Internet.write(out)?;
-        write_u32_be(out, 300)?; // TTL in seconds.
-        match self {
-            DnsRecord::A(_, ipv4_addr) => Self::write_rdata(&ipv4_addr.octets(), out),
-            DnsRecord::AAAA(_, ipv6_addr) => Self::write_rdata(&ipv6_addr.octets(), out),
-            DnsRecord::CNAME(_, target_name) => {
-                Self::write_rdata(target_name.as_bytes()?.readable(), out)
+        self.class.write(out)?;
+        write_u32_be(out, self.ttl_seconds)?;
+        self.data.write_compressed(out, compressor)
+    }
+}
+impl Debug for DnsRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        let (name, class, ttl) = (&self.name, self.class, self.ttl_seconds);
+        match &self.data {
+            DnsRecordData::A(addr) => write!(f, "DnsRecord::A({name},{class},{ttl},{addr})"),
+            DnsRecordData::AAAA(addr) => {
+                write!(f, "DnsRecord::AAAA({name},{class},{ttl},{addr})")
+            }
+            DnsRecordData::CNAME(target) => {
+                write!(f, "DnsRecord::CNAME({name},{class},{ttl},{target})")
             }
-            DnsRecord::Unknown(_, _) => {
-                Err(DnsError::Internal(format!("cannot write record {self:?}")))
+            DnsRecordData::NS(name_server) => {
+                write!(f, "DnsRecord::NS({name},{class},{ttl},{name_server})")
+            }
+            DnsRecordData::PTR(target) => {
+                write!(f, "DnsRecord::PTR({name},{class},{ttl},{target})")
+            }
+            DnsRecordData::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => write!(
+                f,
+                "DnsRecord::SOA({name},{class},{ttl},{mname},{rname},{serial},{refresh},{retry},{expire},{minimum})"
+            ),
+            DnsRecordData::MX {
+                preference,
+                exchange,
+            } => write!(
+                f,
+                "DnsRecord::MX({name},{class},{ttl},{preference},{exchange})"
+            ),
+            DnsRecordData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(
+                f,
+                "DnsRecord::SRV({name},{class},{ttl},{priority},{weight},{port},{target})"
+            ),
+            DnsRecordData::TXT(_) => {
+                write!(f, "DnsRecord::TXT({name},{class},{ttl},{})", self.data)
+            }
+            DnsRecordData::Unknown(typ, rdata) => {
+                let rdata = DnsRecordData::format_generic_rdata(rdata);
+                write!(f, "DnsRecord::Unknown({name},{class},{ttl},{typ},{rdata})")
             }
         }
     }
 }
-impl Debug for DnsRecord {
+/// Writes the record in zone file presentation form, like `a.b.\t300\tIN\tA\t10.0.0.1`.
+///
+/// > `<domain-name> [<TTL>] [<class>] <type> <RDATA>`
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-5.1>
+impl Display for DnsRecord {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
-        match self {
-            DnsRecord::A(name, addr) => write!(f, "DnsRecord::A({name},{addr})"),
-            DnsRecord::AAAA(name, addr) => write!(f, "DnsRecord::AAAA({name},{addr})"),
-            DnsRecord::CNAME(name, target) => write!(f, "DnsRecord::CNAME({name},{target})"),
-            DnsRecord::Unknown(name, typ) => write!(f, "DnsRecord::Unknown({name},{typ})"),
+        write!(
+            f,
+            "{}.\t{}\t{}\t{}\t{}",
+            self.name,
+            self.ttl_seconds,
+            self.class,
+            self.typ(),
+            self.data
+        )
+    }
+}
+/// Parses a record in zone file presentation form.  The TTL and class are optional and may come
+/// in either order.  They default to [`DnsRecord::DEFAULT_TTL_SECONDS`] and `IN`.
+impl FromStr for DnsRecord {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        /// Splits off the first word.
+        fn next_word(value: &str) -> Option<(&str, &str)> {
+            let value = value.trim_start_matches(|c: char| c.is_ascii_whitespace());
+            let end = value
+                .find(|c: char| c.is_ascii_whitespace())
+                .unwrap_or(value.len());
+            (end != 0).then(|| value.split_at(end))
         }
+        let (name, mut rest) =
+            next_word(value).ok_or_else(|| format!("record has no name: {value:?}"))?;
+        let name: DnsName = name.parse()?;
+        let mut ttl_seconds = None;
+        let mut class = None;
+        let typ: DnsType = loop {
+            let (word, tail) =
+                next_word(rest).ok_or_else(|| format!("record has no type: {value:?}"))?;
+            rest = tail;
+            if ttl_seconds.is_none() && word.bytes().all(|b| b.is_ascii_digit()) {
+                ttl_seconds = Some(
+                    word.parse()
+                        .map_err(|e| format!("failed parsing TTL {word:?}: {e}"))?,
+                );
+            } else if let (None, Ok(c)) = (class, word.parse::<DnsClass>()) {
+                class = Some(c);
+            } else {
+                break word.parse()?;
+            }
+        };
+        Ok(Self::new(
+            name,
+            class.unwrap_or(DnsClass::Internet),
+            ttl_seconds.unwrap_or(Self::DEFAULT_TTL_SECONDS),
+            // The RDATA is the rest of the line, since TXT strings may hold spaces.
+            DnsRecordData::parse(&typ, rest)?,
+        ))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsRecord {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let name = u.arbitrary()?;
+        // Reading rejects other classes.
+        let class = if u.arbitrary()? {
+            DnsClass::Internet
+        } else {
+            DnsClass::Any
+        };
+        // Reading treats TTLs with the most significant bit set as zero.
+        let ttl_seconds = u.int_in_range(0..=0x7FFF_FFFF)?;
+        Ok(Self::new(name, class, ttl_seconds, u.arbitrary()?))
     }
 }
 
 #[cfg(test)]
 #[test]
 fn test_dns_record() {
-    use std::net::{Ipv4Addr, Ipv6Addr};
+    use core::net::{Ipv4Addr, Ipv6Addr};
     // Constructors
     assert_eq!(
-        DnsRecord::A(DnsName::new("a.b").unwrap(), Ipv4Addr::new(1, 2, 3, 4)),
+        DnsRecord::new(
+            DnsName::new("a.b").unwrap(),
+            DnsClass::Internet,
+            300,
+            DnsRecordData::A(Ipv4Addr::new(1, 2, 3, 4))
+        ),
         DnsRecord::new_a("a.b", "1.2.3.4").unwrap()
     );
     assert_eq!(
-        DnsRecord::AAAA(
+        DnsRecord::new(
             DnsName::new("a.b").unwrap(),
-            Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0)
+            DnsClass::Internet,
+            300,
+            DnsRecordData::AAAA(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0))
         ),
         DnsRecord::new_aaaa("a.b", "2001:db8::").unwrap()
     );
     assert_eq!(
-        DnsRecord::CNAME(DnsName::new("a.b").unwrap(), DnsName::new("c.d").unwrap()),
+        DnsRecord::new(
+            DnsName::new("a.b").unwrap(),
+            DnsClass::Internet,
+            300,
+            DnsRecordData::CNAME(DnsName::new("c.d").unwrap())
+        ),
         DnsRecord::new_cname("a.b", "c.d").unwrap()
     );
+    assert_eq!(
+        DnsRecord::new(
+            DnsName::new("a.b").unwrap(),
+            DnsClass::Internet,
+            300,
+            DnsRecordData::MX {
+                preference: 10,
+                exchange: DnsName::new("c.d").unwrap(),
+            }
+        ),
+        DnsRecord::new_mx("a.b", 10, "c.d").unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new(
+            DnsName::new("a.b").unwrap(),
+            DnsClass::Internet,
+            300,
+            DnsRecordData::NS(DnsName::new("ns1.a.b").unwrap())
+        ),
+        DnsRecord::new_ns("a.b", "ns1.a.b").unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new(
+            DnsName::new("1.0.0.10.in-addr.arpa").unwrap(),
+            DnsClass::Internet,
+            300,
+            DnsRecordData::PTR(DnsName::new("a.b").unwrap())
+        ),
+        DnsRecord::new_ptr("1.0.0.10.in-addr.arpa", "a.b").unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new(
+            DnsName::new("_sip._tcp.a.b").unwrap(),
+            DnsClass::Internet,
+            300,
+            DnsRecordData::SRV {
+                priority: 10,
+                weight: 60,
+                port: 5060,
+                target: DnsName::new("sip.a.b").unwrap(),
+            }
+        ),
+        DnsRecord::new_srv("_sip._tcp.a.b", 10, 60, 5060, "sip.a.b").unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new(
+            DnsName::new("a.b").unwrap(),
+            DnsClass::Internet,
+            300,
+            DnsRecordData::SOA {
+                mname: DnsName::new("ns1.a.b").unwrap(),
+                rname: DnsName::new("admin.a.b").unwrap(),
+                serial: 1,
+                refresh: 7200,
+                retry: 3600,
+                expire: 1_209_600,
+                minimum: 60,
+            }
+        ),
+        DnsRecord::new_soa("a.b", "ns1.a.b", "admin.a.b", 1, 7200, 3600, 1_209_600, 60).unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new(
+            DnsName::new("a.b").unwrap(),
+            DnsClass::Internet,
+            300,
+            DnsRecordData::TXT(vec![b"hi".to_vec()])
+        ),
+        DnsRecord::new_txt("a.b", "hi").unwrap()
+    );
+    // Accessors
+    let record = DnsRecord::new_a("a.b", "1.2.3.4")
+        .unwrap()
+        .with_ttl(60)
+        .with_class(DnsClass::Any);
+    assert_eq!("a.b", record.name().inner());
+    assert_eq!(DnsType::A, record.typ());
+    assert_eq!(DnsClass::Any, record.class());
+    assert_eq!(60, record.ttl_seconds());
+    assert_eq!(&DnsRecordData::A(Ipv4Addr::new(1, 2, 3, 4)), record.data());
     // Debug
     assert_eq!(
-        "DnsRecord::A(a.b,1.2.3.4)",
+        "DnsRecord::A(a.b,IN,300,1.2.3.4)",
+        format!("{:?}", DnsRecord::new_a("a.b", "1.2.3.4").unwrap())
+    );
+    assert_eq!(
+        "DnsRecord::AAAA(a.b,IN,300,2001:db8::)",
+        format!("{:?}", DnsRecord::new_aaaa("a.b", "2001:db8::").unwrap())
+    );
+    assert_eq!(
+        "DnsRecord::CNAME(a.b,ANY,60,c.d)",
         format!(
             "{:?}",
-            DnsRecord::A(DnsName::new("a.b").unwrap(), Ipv4Addr::new(1, 2, 3, 4))
+            DnsRecord::new_cname("a.b", "c.d")
+                .unwrap()
+                .with_class(DnsClass::Any)
+                .with_ttl(60)
         )
     );
     assert_eq!(
-        "DnsRecord::AAAA(a.b,2001:db8::)",
+        "DnsRecord::MX(a.b,IN,300,10,c.d)",
+        format!("{:?}", DnsRecord::new_mx("a.b", 10, "c.d").unwrap())
+    );
+    assert_eq!(
+        "DnsRecord::NS(a.b,IN,300,ns1.a.b)",
+        format!("{:?}", DnsRecord::new_ns("a.b", "ns1.a.b").unwrap())
+    );
+    assert_eq!(
+        "DnsRecord::SRV(_sip._tcp.a.b,IN,300,10,60,5060,sip.a.b)",
+        format!(
+            "{:?}",
+            DnsRecord::new_srv("_sip._tcp.a.b", 10, 60, 5060, "sip.a.b").unwrap()
+        )
+    );
+    assert_eq!(
+        "DnsRecord::PTR(1.0.0.10.in-addr.arpa,IN,300,a.b)",
         format!(
             "{:?}",
-            DnsRecord::AAAA(
-                DnsName::new("a.b").unwrap(),
-                Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0)
-            )
+            DnsRecord::new_ptr("1.0.0.10.in-addr.arpa", "a.b").unwrap()
         )
     );
     assert_eq!(
-        "DnsRecord::CNAME(a.b,c.d)",
+        "DnsRecord::TXT(a.b,IN,300,\"hi\")",
+        format!("{:?}", DnsRecord::new_txt("a.b", "hi").unwrap())
+    );
+    assert_eq!(
+        "DnsRecord::SOA(a.b,IN,300,ns1.a.b,admin.a.b,1,7200,3600,1209600,60)",
         format!(
             "{:?}",
-            DnsRecord::CNAME(DnsName::new("a.b").unwrap(), DnsName::new("c.d").unwrap())
+            DnsRecord::new_soa("a.b", "ns1.a.b", "admin.a.b", 1, 7200, 3600, 1_209_600, 60)
+                .unwrap()
         )
     );
 }
+
+#[cfg(test)]
+#[test]
+fn test_read_write_ttl() {
+    let record = DnsRecord::new_a("a.b", "10.0.0.1").unwrap().with_ttl(86400);
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    record.write(&mut buf).unwrap();
+    assert_eq!(
+        [1, b'a', 1, b'b', 0, 0, 1, 0, 1, 0, 1, 0x51, 0x80, 0, 4, 10, 0, 0, 1],
+        buf.readable()
+    );
+    assert_eq!(record, DnsRecord::read(&mut buf).unwrap());
+    // TTLs with the most significant bit set are zero.
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[1, b'a', 0, 0, 1, 0, 1, 0x80, 0, 0, 1, 0, 4, 10, 0, 0, 1])
+        .unwrap();
+    assert_eq!(0, DnsRecord::read(&mut buf).unwrap().ttl_seconds());
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_compressed() {
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[
+        // 0: a.b
+        1, b'a', 1, b'b', 0, // name
+        0, 5, 0, 1, 0, 0, 0, 60, // CNAME IN ttl=60
+        0, 4, 1, b'c', 0xC0, 2, // rdata=c.b
+        // 19: A record for c.b
+        0xC0, 15, // name
+        0, 1, 0, 1, 0, 0, 0, 60, // A IN ttl=60
+        0, 4, 10, 0, 0, 1, // rdata=10.0.0.1
+    ])
+    .unwrap();
+    assert_eq!(
+        DnsRecord::new_cname("a.b", "c.b").unwrap().with_ttl(60),
+        DnsRecord::read(&mut buf).unwrap()
+    );
+    assert_eq!(
+        DnsRecord::new_a("c.b", "10.0.0.1").unwrap().with_ttl(60),
+        DnsRecord::read(&mut buf).unwrap()
+    );
+    assert!(buf.is_empty());
+    // MX RDATA with a compressed exchange.
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[
+        1, b'a', 1, b'b', 0, // name
+        0, 15, 0, 1, 0, 0, 0, 60, // MX IN ttl=60
+        0, 6, 0, 10, 1, b'm', 0xC0, 0, // rdata=10 m.a.b
+    ])
+    .unwrap();
+    assert_eq!(
+        DnsRecord::new_mx("a.b", 10, "m.a.b").unwrap().with_ttl(60),
+        DnsRecord::read(&mut buf).unwrap()
+    );
+    // NS RDATA with a compressed name.
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[
+        1, b'a', 1, b'b', 0, // name
+        0, 2, 0, 1, 0, 0, 0, 60, // NS IN ttl=60
+        0, 5, 2, b'n', b's', 0xC0, 0, // rdata=ns.a.b
+    ])
+    .unwrap();
+    assert_eq!(
+        DnsRecord::new_ns("a.b", "ns.a.b").unwrap().with_ttl(60),
+        DnsRecord::read(&mut buf).unwrap()
+    );
+    // RDLENGTH does not match the RDATA.
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[1, b'a', 0, 0, 1, 0, 1, 0, 0, 0, 60, 0, 5, 10, 0, 0, 1, 0])
+        .unwrap();
+    assert_eq!(Err(DnsError::InvalidRdata), DnsRecord::read(&mut buf));
+}
+
+#[cfg(test)]
+#[test]
+fn test_unknown_type() {
+    let record = DnsRecord::new_generic("a.b", DnsType::Unknown(65534), "\\# 3 abcdef").unwrap();
+    assert_eq!(DnsType::Unknown(65534), record.typ());
+    assert_eq!(
+        "DnsRecord::Unknown(a.b,IN,300,TYPE65534,\\# 3 ABCDEF)",
+        format!("{record:?}")
+    );
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    record.write(&mut buf).unwrap();
+    assert_eq!(
+        [1, b'a', 1, b'b', 0, 0xFF, 0xFE, 0, 1, 0, 0, 1, 0x2C, 0, 3, 0xAB, 0xCD, 0xEF],
+        buf.readable()
+    );
+    assert_eq!(record, DnsRecord::read(&mut buf).unwrap());
+    assert_eq!(
+        DnsRecord::new_a("a.b", "10.0.0.1").unwrap(),
+        DnsRecord::new_generic("a.b", DnsType::A, "\\# 4 0A000001").unwrap()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    let record = DnsRecord::new_a("a.b", "10.0.0.1").unwrap();
+    assert_eq!("a.b.\t300\tIN\tA\t10.0.0.1", record.to_string());
+    assert_eq!(Ok(record.clone()), record.to_string().parse());
+    assert_eq!(Ok(record.clone()), "a.b A 10.0.0.1".parse());
+    let record = DnsRecord::new_cname("a.b", "c.d")
+        .unwrap()
+        .with_ttl(60)
+        .with_class(DnsClass::Any);
+    assert_eq!("a.b.\t60\tANY\tCNAME\tc.d.", record.to_string());
+    assert_eq!(Ok(record.clone()), "a.b. ANY 60 CNAME c.d.".parse());
+    let record = DnsRecord::new_generic("a.b", DnsType::Unknown(65534), "\\# 3 abcdef").unwrap();
+    assert_eq!("a.b.\t300\tIN\tTYPE65534\t\\# 3 ABCDEF", record.to_string());
+    assert_eq!(Ok(record.clone()), record.to_string().parse());
+    let record = DnsRecord::new_mx("a.b", 10, "m.a.b").unwrap();
+    assert_eq!("a.b.\t300\tIN\tMX\t10 m.a.b.", record.to_string());
+    assert_eq!(Ok(record.clone()), record.to_string().parse());
+    let record = DnsRecord::new_ns("a.b", "ns1.a.b").unwrap();
+    assert_eq!("a.b.\t300\tIN\tNS\tns1.a.b.", record.to_string());
+    assert_eq!(Ok(record.clone()), record.to_string().parse());
+    let record = DnsRecord::new_srv("_sip._tcp.a.b", 10, 60, 5060, "sip.a.b").unwrap();
+    assert_eq!(
+        "_sip._tcp.a.b.\t300\tIN\tSRV\t10 60 5060 sip.a.b.",
+        record.to_string()
+    );
+    assert_eq!(Ok(record.clone()), record.to_string().parse());
+    let record = DnsRecord::new_ptr("1.0.0.10.in-addr.arpa", "a.b").unwrap();
+    assert_eq!(
+        "1.0.0.10.in-addr.arpa.\t300\tIN\tPTR\ta.b.",
+        record.to_string()
+    );
+    assert_eq!(Ok(record.clone()), record.to_string().parse());
+    let record =
+        DnsRecord::new_soa("a.b", "ns1.a.b", "admin.a.b", 1, 7200, 3600, 1_209_600, 60).unwrap();
+    assert_eq!(
+        "a.b.\t300\tIN\tSOA\tns1.a.b. admin.a.b. 1 7200 3600 1209600 60",
+        record.to_string()
+    );
+    assert_eq!(Ok(record.clone()), record.to_string().parse());
+    let record = DnsRecord::new_txt("a.b", "v=spf1  include:c.d -all").unwrap();
+    assert_eq!(
+        "a.b.\t300\tIN\tTXT\t\"v=spf1  include:c.d -all\"",
+        record.to_string()
+    );
+    assert_eq!(Ok(record.clone()), record.to_string().parse());
+    "a.b 60 IN".parse::<DnsRecord>().unwrap_err();
+    "a.b 60 IN A".parse::<DnsRecord>().unwrap_err();
+    "a.b IN 60 A 10.0.0.1 extra"
+        .parse::<DnsRecord>()
+        .unwrap_err();
+    "a.b 10.0.0.1".parse::<DnsRecord>().unwrap_err();
+}
+
+#[cfg(test)]
+#[test]
+fn test_write_compressed_mx() {
+    let record = DnsRecord::new_mx("a.b", 10, "m.a.b").unwrap().with_ttl(60);
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    let mut compressor = DnsNameCompressor::new(&out);
+    record.write_compressed(&mut out, &mut compressor).unwrap();
+    assert_eq!(
+        [
+            1, b'a', 1, b'b', 0, // name
+            0, 15, 0, 1, 0, 0, 0, 60, // MX IN ttl=60
+            0, 6, 0, 10, 1, b'm', 0xC0, 0, // rdata=10 m.a.b
+        ],
+        out.readable()
+    );
+    assert_eq!(record, DnsRecord::read(&mut out).unwrap());
+}
+
+#[cfg(test)]
+#[test]
+fn test_write_compressed_soa() {
+    let record = DnsRecord::new_soa("a.b", "ns.a.b", "admin.a.b", 1, 2, 3, 4, 5)
+        .unwrap()
+        .with_ttl(60);
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    let mut compressor = DnsNameCompressor::new(&out);
+    record.write_compressed(&mut out, &mut compressor).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(
+        [
+            1, b'a', 1, b'b', 0, // name
+            0, 6, 0, 1, 0, 0, 0, 60, // SOA IN ttl=60
+            0, 33, // RDLENGTH
+            2, b'n', b's', 0xC0, 0, // 15: mname=ns.a.b
+            5, b'a', b'd', b'm', b'i', b'n', 0xC0, 0, // rname=admin.a.b
+            0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5,
+        ],
+        out.readable()
+    );
+    assert_eq!(record, DnsRecord::read(&mut out).unwrap());
+}
+
+#[cfg(test)]
+#[test]
+fn test_write_srv_uncompressed() {
+    let record = DnsRecord::new_srv("_sip._tcp.a.b", 10, 60, 5060, "a.b")
+        .unwrap()
+        .with_ttl(60);
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    let mut compressor = DnsNameCompressor::new(&out);
+    record.write_compressed(&mut out, &mut compressor).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(
+        [
+            4, b'_', b's', b'i', b'p', 4, b'_', b't', b'c', b'p', 1, b'a', 1, b'b', 0, // name
+            0, 33, 0, 1, 0, 0, 0, 60, // SRV IN ttl=60
+            0, 11, // RDLENGTH
+            0, 10, 0, 60, 0x13, 0xC4, // priority=10 weight=60 port=5060
+            1, b'a', 1, b'b', 0, // target=a.b, not compressed
+        ],
+        out.readable()
+    );
+    assert_eq!(record, DnsRecord::read(&mut out).unwrap());
+}
@@ -0,0 +1,933 @@
+use crate::dns_name_compressor::DnsNameCompressor;
+use crate::{
+    hex_decode, hex_encode, read_offset, write_u16_be, write_u32_be, DnsError, DnsName, DnsNameRef,
+    DnsRecord, DnsType, FixedBuf,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// The type-specific RDATA of a [`DnsRecord`](crate::DnsRecord).
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum DnsRecordData {
+    A(Ipv4Addr),
+    AAAA(Ipv6Addr),
+    CNAME(DnsName),
+    /// A name server for the owner name's zone.
+    ///
+    /// > NSDNAME         A `<domain-name>` which specifies a host which should be authoritative
+    /// >                 for the specified class and domain.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.11>
+    NS(DnsName),
+    /// A name that the owner name points to, usually the host for an address in `in-addr.arpa`
+    /// or `ip6.arpa`.
+    ///
+    /// > PTRDNAME        A `<domain-name>` which points to some location in the domain name
+    /// >                 space.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.12>
+    PTR(DnsName),
+    /// A mail exchange, with its preference.  Lower preferences are tried first.
+    ///
+    /// > ```text
+    /// >     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+    /// >     |                  PREFERENCE                   |
+    /// >     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+    /// >     /                   EXCHANGE                    /
+    /// >     /                                               /
+    /// >     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+    /// > ```
+    /// >
+    /// > where:
+    /// >
+    /// > PREFERENCE      A 16 bit integer which specifies the preference given to this RR among
+    /// >                 others at the same owner.  Lower values are preferred.
+    /// >
+    /// > EXCHANGE        A `<domain-name>` which specifies a host willing to act as a mail
+    /// >                 exchange for the owner name.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.9>
+    MX {
+        preference: u16,
+        exchange: DnsName,
+    },
+    /// The start of a zone of authority.
+    ///
+    /// > MNAME           The `<domain-name>` of the name server that was the original or primary
+    /// >                 source of data for this zone.
+    /// >
+    /// > RNAME           A `<domain-name>` which specifies the mailbox of the person responsible
+    /// >                 for this zone.
+    /// >
+    /// > SERIAL          The unsigned 32 bit version number of the original copy of the zone.
+    /// >                 Zone transfers preserve this value.  This value wraps and should be
+    /// >                 compared using sequence space arithmetic.
+    /// >
+    /// > REFRESH         A 32 bit time interval before the zone should be refreshed.
+    /// >
+    /// > RETRY           A 32 bit time interval that should elapse before a failed refresh
+    /// >                 should be retried.
+    /// >
+    /// > EXPIRE          A 32 bit time value that specifies the upper limit on the time interval
+    /// >                 that can elapse before the zone is no longer authoritative.
+    /// >
+    /// > MINIMUM         The unsigned 32 bit minimum TTL field that should be exported with any
+    /// >                 RR from this zone.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.13>
+    ///
+    /// RFC 2308 made `minimum` the TTL of negative answers from the zone.
+    SOA {
+        mname: DnsName,
+        rname: DnsName,
+        serial: u32,
+        refresh: u32,
+        retry: u32,
+        expire: u32,
+        minimum: u32,
+    },
+    /// The host and port of a server for the service and protocol in the owner name, like
+    /// `_sip._tcp.example.com`.
+    ///
+    /// > Priority
+    /// >     The priority of this target host.  A client MUST attempt to contact the target host
+    /// >     with the lowest-numbered priority it can reach; target hosts with the same priority
+    /// >     SHOULD be tried in an order defined by the weight field.
+    /// >
+    /// > Weight
+    /// >     A server selection mechanism.  The weight field specifies a relative weight for
+    /// >     entries with the same priority.  Larger weights SHOULD be given a proportionately
+    /// >     higher probability of being selected.
+    /// >
+    /// > Port
+    /// >     The port on this target host of this service.
+    /// >
+    /// > Target
+    /// >     The domain name of the target host.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2782>
+    SRV {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: DnsName,
+    },
+    /// Descriptive text, as one or more strings of up to 255 octets each.
+    ///
+    /// > TXT-DATA        One or more `<character-string>`s.
+    /// >
+    /// > TXT RRs are used to hold descriptive text.  The semantics of the text depends on the
+    /// > domain where it is found.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.14>
+    ///
+    /// > `<character-string>` is a single length octet followed by that number of characters.
+    /// > `<character-string>` is treated as binary information, and can be up to 256 characters
+    /// > in length (including the length octet).
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3>
+    TXT(Vec<Vec<u8>>),
+    /// RDATA of a type we do not model, kept as raw bytes.
+    ///
+    /// > In the absence of a new RR type being explicitly defined as containing compressed domain
+    /// > names, receiving servers MUST treat the RDATA of unknown RR types as opaque octet strings.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc3597#section-4>
+    Unknown(DnsType, Vec<u8>),
+}
+impl DnsRecordData {
+    #[must_use]
+    pub fn typ(&self) -> DnsType {
+        match self {
+            DnsRecordData::A(_) => DnsType::A,
+            DnsRecordData::AAAA(_) => DnsType::AAAA,
+            DnsRecordData::CNAME(_) => DnsType::CNAME,
+            DnsRecordData::MX { .. } => DnsType::MX,
+            DnsRecordData::NS(_) => DnsType::NS,
+            DnsRecordData::PTR(_) => DnsType::PTR,
+            DnsRecordData::SOA { .. } => DnsType::SOA,
+            DnsRecordData::SRV { .. } => DnsType::SRV,
+            DnsRecordData::TXT(_) => DnsType::TXT,
+            DnsRecordData::Unknown(typ, _) => typ.clone(),
+        }
+    }
+
+    /// Parses RDATA in the generic format for unknown types.
+    ///
+    /// > The RDATA section of an RR of unknown type is represented as a sequence of white space
+    /// > separated words as follows:
+    /// >
+    /// > - The special token `\#` (a backslash immediately followed by a hash sign), which
+    /// >   identifies the RDATA as having the generic encoding defined herein rather than a
+    /// >   traditional type-specific encoding.
+    /// > - An unsigned decimal integer specifying the RDATA length in octets.
+    /// > - Zero or more words of hexadecimal data encoding the actual RDATA field, each containing
+    /// >   an even number of hexadecimal digits.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc3597#section-5>
+    ///
+    /// Types we model are parsed into their own variants, so `\# 4 0A000001` with type `A`
+    /// becomes [`DnsRecordData::A`].
+    ///
+    /// # Errors
+    /// Returns an error when `value` is not valid generic RDATA for `typ`.
+    pub fn parse_generic(typ: &DnsType, value: &str) -> Result<Self, String> {
+        let mut words = value.split_ascii_whitespace();
+        if words.next() != Some("\\#") {
+            return Err(format!("generic RDATA does not start with \\#: {value:?}"));
+        }
+        // RDLENGTH is 16 bits.
+        let len: usize = words
+            .next()
+            .and_then(|word| word.parse::<u16>().ok())
+            .map(usize::from)
+            .ok_or_else(|| format!("generic RDATA has no valid length: {value:?}"))?;
+        let mut bytes = Vec::with_capacity(len);
+        for word in words {
+            if word.len() % 2 != 0 {
+                return Err(format!(
+                    "generic RDATA has odd number of hex digits: {value:?}"
+                ));
+            }
+            bytes.extend(
+                hex_decode(word)
+                    .ok_or_else(|| format!("generic RDATA has invalid hex: {value:?}"))?,
+            );
+        }
+        if bytes.len() != len {
+            return Err(format!(
+                "generic RDATA length {len} does not match {} bytes of data: {value:?}",
+                bytes.len()
+            ));
+        }
+        Self::read_at(typ, &bytes, 0, len)
+            .map_err(|e| format!("invalid RDATA for type {typ}: {e:?}: {value:?}"))
+    }
+
+    /// Splits `text` into character-strings of at most 255 octets, for a
+    /// [`DnsRecordData::TXT`].  Empty text becomes one empty string.
+    #[must_use]
+    pub fn txt(text: &[u8]) -> Self {
+        if text.is_empty() {
+            return DnsRecordData::TXT(vec![Vec::new()]);
+        }
+        DnsRecordData::TXT(text.chunks(255).map(<[u8]>::to_vec).collect())
+    }
+
+    /// Parses character-strings in presentation form.
+    ///
+    /// > `<character-string>` is expressed in one or two ways: as a contiguous set of characters
+    /// > without interior spaces, or as a string beginning with a " and ending with a ".  Inside
+    /// > a " delimited string any character can occur, except for a " itself, which must be
+    /// > quoted using \ (back slash).
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc1035#section-5.1>
+    ///
+    /// Both forms accept the `\X` and `\DDD` escapes that names use.
+    fn parse_character_strings(value: &str) -> Result<Vec<Vec<u8>>, String> {
+        let err = || format!("not valid character-strings: {value:?}");
+        let mut strings = Vec::new();
+        let mut bytes = value.bytes().peekable();
+        loop {
+            while bytes.next_if(u8::is_ascii_whitespace).is_some() {}
+            let Some(first) = bytes.peek().copied() else {
+                return Ok(strings);
+            };
+            let quoted = first == b'"';
+            if quoted {
+                bytes.next();
+            }
+            let mut string = Vec::new();
+            loop {
+                match bytes.next() {
+                    None if quoted => return Err(err()),
+                    None => break,
+                    Some(b'"') if quoted => break,
+                    Some(b) if !quoted && (b.is_ascii_whitespace() || b == b'"') => {
+                        return Err(err())
+                    }
+                    Some(b'\\') => match bytes.next() {
+                        Some(d) if d.is_ascii_digit() => {
+                            let mut octet = u16::from(d - b'0');
+                            for _ in 0..2 {
+                                match bytes.next() {
+                                    Some(d) if d.is_ascii_digit() => {
+                                        octet = octet * 10 + u16::from(d - b'0');
+                                    }
+                                    _ => return Err(err()),
+                                }
+                            }
+                            string.push(u8::try_from(octet).map_err(|_| err())?);
+                        }
+                        Some(c) => string.push(c),
+                        None => return Err(err()),
+                    },
+                    Some(b) => string.push(b),
+                }
+                if !quoted && bytes.peek().is_none_or(u8::is_ascii_whitespace) {
+                    break;
+                }
+            }
+            if string.len() > 255 {
+                return Err(format!(
+                    "character-string is longer than 255 octets: {value:?}"
+                ));
+            }
+            strings.push(string);
+        }
+    }
+
+    /// Writes `string` in quotes, escaping quotes, backslashes, and octets that are not printable
+    /// ASCII.
+    fn fmt_character_string(f: &mut Formatter<'_>, string: &[u8]) -> core::fmt::Result {
+        write!(f, "\"")?;
+        for b in string {
+            match b {
+                b'"' | b'\\' => write!(f, "\\{}", char::from(*b))?,
+                b' ' => write!(f, " ")?,
+                b if b.is_ascii_graphic() => write!(f, "{}", char::from(*b))?,
+                b => write!(f, "\\{b:03}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+
+    /// Formats `rdata` in the generic `\# <length> <hex>` format.
+    #[must_use]
+    pub fn format_generic_rdata(rdata: &[u8]) -> String {
+        let mut result = format!("\\# {}", rdata.len());
+        if !rdata.is_empty() {
+            result.push(' ');
+            result.push_str(&hex_encode(rdata));
+        }
+        result
+    }
+
+    /// Parses RDATA of type `typ` in presentation form, like `10.0.0.1` for an `A` record.
+    /// RDATA in the generic format, starting with `\#`, is parsed with
+    /// [`DnsRecordData::parse_generic`].  Types we do not model accept only the generic format.
+    ///
+    /// # Errors
+    /// Returns an error when `value` is not valid RDATA for `typ`.
+    pub fn parse(typ: &DnsType, value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if value.starts_with("\\#") {
+            return Self::parse_generic(typ, value);
+        }
+        match typ {
+            DnsType::A => value
+                .parse()
+                .map(DnsRecordData::A)
+                .map_err(|e| format!("failed parsing {value:?} as an IPv4 address: {e}")),
+            DnsType::AAAA => value
+                .parse()
+                .map(DnsRecordData::AAAA)
+                .map_err(|e| format!("failed parsing {value:?} as an IPv6 address: {e}")),
+            DnsType::CNAME => value.parse().map(DnsRecordData::CNAME),
+            DnsType::NS => value.parse().map(DnsRecordData::NS),
+            DnsType::PTR => value.parse().map(DnsRecordData::PTR),
+            DnsType::SOA => {
+                let err = || {
+                    format!(
+                        "SOA RDATA is not `<mname> <rname> <serial> <refresh> <retry> <expire> <minimum>`: {value:?}"
+                    )
+                };
+                let words: Vec<&str> = value.split_ascii_whitespace().collect();
+                let [mname, rname, numbers @ ..] = words.as_slice() else {
+                    return Err(err());
+                };
+                let numbers = numbers
+                    .iter()
+                    .map(|word| word.parse::<u32>().map_err(|_| err()))
+                    .collect::<Result<Vec<u32>, String>>()?;
+                let [serial, refresh, retry, expire, minimum] = numbers.as_slice() else {
+                    return Err(err());
+                };
+                Ok(DnsRecordData::SOA {
+                    mname: mname.parse()?,
+                    rname: rname.parse()?,
+                    serial: *serial,
+                    refresh: *refresh,
+                    retry: *retry,
+                    expire: *expire,
+                    minimum: *minimum,
+                })
+            }
+            DnsType::SRV => {
+                let err =
+                    || format!("SRV RDATA is not `<priority> <weight> <port> <target>`: {value:?}");
+                let words: Vec<&str> = value.split_ascii_whitespace().collect();
+                let [priority, weight, port, target] = words.as_slice() else {
+                    return Err(err());
+                };
+                Ok(DnsRecordData::SRV {
+                    priority: priority.parse().map_err(|_| err())?,
+                    weight: weight.parse().map_err(|_| err())?,
+                    port: port.parse().map_err(|_| err())?,
+                    target: target.parse()?,
+                })
+            }
+            DnsType::TXT => Self::parse_character_strings(value).and_then(|strings| {
+                if strings.is_empty() {
+                    Err(format!("TXT RDATA has no character-strings: {value:?}"))
+                } else {
+                    Ok(DnsRecordData::TXT(strings))
+                }
+            }),
+            DnsType::MX => {
+                let err = || format!("MX RDATA is not `<preference> <exchange>`: {value:?}");
+                let mut words = value.split_ascii_whitespace();
+                let (Some(preference), Some(exchange), None) =
+                    (words.next(), words.next(), words.next())
+                else {
+                    return Err(err());
+                };
+                Ok(DnsRecordData::MX {
+                    preference: preference.parse().map_err(|_| err())?,
+                    exchange: exchange.parse()?,
+                })
+            }
+            _ => Err(format!(
+                "{typ} RDATA must use the generic \\# format: {value:?}"
+            )),
+        }
+    }
+
+    /// Reads `rdata_len` bytes of RDATA of type `typ`.
+    ///
+    /// RDATA is parsed in place so names in it can use compression pointers.
+    ///
+    /// # Errors
+    /// Returns an error when `buf` does not contain valid RDATA.
+    pub fn read<const N: usize>(
+        typ: &DnsType,
+        rdata_len: usize,
+        buf: &mut FixedBuf<N>,
+    ) -> Result<Self, DnsError> {
+        let start = read_offset(buf);
+        let message = &buf.mem()[..start + buf.len()];
+        let data = Self::read_at(typ, message, start, rdata_len)?;
+        buf.read_bytes(rdata_len);
+        Ok(data)
+    }
+
+    /// Reads the `rdata_len` bytes of RDATA of type `typ` at `offset` in `message`.
+    ///
+    /// `message` must start at the first byte of the message, so names in the RDATA can use
+    /// compression pointers.
+    ///
+    /// # Errors
+    /// Returns an error when `message` does not contain valid RDATA at `offset`.
+    pub fn read_at(
+        typ: &DnsType,
+        message: &[u8],
+        offset: usize,
+        rdata_len: usize,
+    ) -> Result<Self, DnsError> {
+        let rdata_end = offset + rdata_len;
+        let rdata = message.get(offset..rdata_end).ok_or(DnsError::Truncated)?;
+        let fixed = |start: usize, len: usize| -> Result<&[u8], DnsError> {
+            message.get(start..start + len).ok_or(DnsError::Truncated)
+        };
+        let (data, end) = match typ {
+            DnsType::A => {
+                let mut octets = [0_u8; 4];
+                octets.copy_from_slice(fixed(offset, 4)?);
+                (DnsRecordData::A(Ipv4Addr::from(octets)), offset + 4)
+            }
+            DnsType::AAAA => {
+                let mut octets = [0_u8; 16];
+                octets.copy_from_slice(fixed(offset, 16)?);
+                (DnsRecordData::AAAA(Ipv6Addr::from(octets)), offset + 16)
+            }
+            // > Servers MUST also be able to handle compressed domain names in the RDATA of the
+            // > RR types defined in RFC 1035.
+            // https://datatracker.ietf.org/doc/html/rfc3597#section-4
+            DnsType::CNAME => {
+                let (target, end) = DnsNameRef::read(message, offset)?;
+                (DnsRecordData::CNAME(target.to_name()), end)
+            }
+            DnsType::NS => {
+                let (name_server, end) = DnsNameRef::read(message, offset)?;
+                (DnsRecordData::NS(name_server.to_name()), end)
+            }
+            DnsType::PTR => {
+                let (target, end) = DnsNameRef::read(message, offset)?;
+                (DnsRecordData::PTR(target.to_name()), end)
+            }
+            DnsType::MX => {
+                let preference = fixed(offset, 2)?;
+                let preference = u16::from_be_bytes([preference[0], preference[1]]);
+                let (exchange, end) = DnsNameRef::read(message, offset + 2)?;
+                (
+                    DnsRecordData::MX {
+                        preference,
+                        exchange: exchange.to_name(),
+                    },
+                    end,
+                )
+            }
+            DnsType::SOA => {
+                let (mname, end) = DnsNameRef::read(message, offset)?;
+                let (rname, end) = DnsNameRef::read(message, end)?;
+                let numbers = fixed(end, 20)?;
+                let number = |n: usize| {
+                    u32::from_be_bytes([
+                        numbers[n * 4],
+                        numbers[n * 4 + 1],
+                        numbers[n * 4 + 2],
+                        numbers[n * 4 + 3],
+                    ])
+                };
+                (
+                    DnsRecordData::SOA {
+                        mname: mname.to_name(),
+                        rname: rname.to_name(),
+                        serial: number(0),
+                        refresh: number(1),
+                        retry: number(2),
+                        expire: number(3),
+                        minimum: number(4),
+                    },
+                    end + 20,
+                )
+            }
+            DnsType::SRV => {
+                let numbers = fixed(offset, 6)?;
+                let number = |n: usize| u16::from_be_bytes([numbers[n * 2], numbers[n * 2 + 1]]);
+                let (target, end) = DnsNameRef::read(message, offset + 6)?;
+                (
+                    DnsRecordData::SRV {
+                        priority: number(0),
+                        weight: number(1),
+                        port: number(2),
+                        target: target.to_name(),
+                    },
+                    end,
+                )
+            }
+            DnsType::TXT => {
+                let mut strings = Vec::new();
+                let mut rest = rdata;
+                while let Some((len, tail)) = rest.split_first() {
+                    let string = tail
+                        .get(..usize::from(*len))
+                        .ok_or(DnsError::InvalidRdata)?;
+                    strings.push(string.to_vec());
+                    rest = &tail[string.len()..];
+                }
+                (DnsRecordData::TXT(strings), rdata_end)
+            }
+            DnsType::OPT | DnsType::ANY | DnsType::Unknown(_) => (
+                DnsRecordData::Unknown(typ.clone(), rdata.to_vec()),
+                rdata_end,
+            ),
+        };
+        if end != rdata_end {
+            return Err(DnsError::InvalidRdata);
+        }
+        Ok(data)
+    }
+
+    /// Returns the RDATA in wire form, without name compression.
+    ///
+    /// # Errors
+    /// Returns an error when a TXT string is longer than 255 octets.
+    #[cfg(feature = "serde")]
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, DnsError> {
+        Ok(match self {
+            DnsRecordData::A(ipv4_addr) => ipv4_addr.octets().to_vec(),
+            DnsRecordData::AAAA(ipv6_addr) => ipv6_addr.octets().to_vec(),
+            DnsRecordData::CNAME(target_name)
+            | DnsRecordData::NS(target_name)
+            | DnsRecordData::PTR(target_name) => target_name.wire().to_vec(),
+            DnsRecordData::MX {
+                preference,
+                exchange,
+            } => {
+                let mut rdata = preference.to_be_bytes().to_vec();
+                rdata.extend(exchange.wire());
+                rdata
+            }
+            DnsRecordData::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => {
+                let mut rdata = mname.wire().to_vec();
+                rdata.extend(rname.wire());
+                for number in [serial, refresh, retry, expire, minimum] {
+                    rdata.extend(number.to_be_bytes());
+                }
+                rdata
+            }
+            DnsRecordData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                let mut rdata = Vec::new();
+                for number in [priority, weight, port] {
+                    rdata.extend(number.to_be_bytes());
+                }
+                rdata.extend(target.wire());
+                rdata
+            }
+            DnsRecordData::TXT(strings) => {
+                Self::txt_rdata(strings).ok_or(DnsError::InvalidRdata)?
+            }
+            DnsRecordData::Unknown(_, rdata) => rdata.clone(),
+        })
+    }
+
+    /// Returns TXT RDATA for `strings`, or `None` when a string is longer than 255 octets.
+    fn txt_rdata(strings: &[Vec<u8>]) -> Option<Vec<u8>> {
+        let mut rdata = Vec::new();
+        for string in strings {
+            rdata.push(u8::try_from(string.len()).ok()?);
+            rdata.extend(string);
+        }
+        Some(rdata)
+    }
+
+    /// Writes RDLENGTH and RDATA.
+    ///
+    /// # Errors
+    /// Returns an error when `buf` is full.
+    pub(crate) fn write_compressed<const N: usize>(
+        &self,
+        out: &mut FixedBuf<N>,
+        compressor: &mut DnsNameCompressor,
+    ) -> Result<(), DnsError> {
+        match self {
+            DnsRecordData::A(ipv4_addr) => DnsRecord::write_rdata(&ipv4_addr.octets(), out),
+            DnsRecordData::AAAA(ipv6_addr) => DnsRecord::write_rdata(&ipv6_addr.octets(), out),
+            DnsRecordData::CNAME(target_name)
+            | DnsRecordData::NS(target_name)
+            | DnsRecordData::PTR(target_name) => {
+                // RDATA starts after the two-byte RDLENGTH.
+                let rdata_offset = compressor.offset(out) + 2;
+                let mut rdata: FixedBuf<512> = FixedBuf::new();
+                compressor.write_at(target_name, rdata_offset, &mut rdata)?;
+                DnsRecord::write_rdata(rdata.readable(), out)
+            }
+            DnsRecordData::MX {
+                preference,
+                exchange,
+            } => {
+                let rdata_offset = compressor.offset(out) + 2;
+                let mut rdata: FixedBuf<512> = FixedBuf::new();
+                write_u16_be(&mut rdata, *preference)?;
+                compressor.write_at(exchange, rdata_offset + 2, &mut rdata)?;
+                DnsRecord::write_rdata(rdata.readable(), out)
+            }
+            DnsRecordData::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => {
+                let rdata_offset = compressor.offset(out) + 2;
+                let mut rdata: FixedBuf<1024> = FixedBuf::new();
+                compressor.write_at(mname, rdata_offset, &mut rdata)?;
+                compressor.write_at(rname, rdata_offset + rdata.len(), &mut rdata)?;
+                for number in [serial, refresh, retry, expire, minimum] {
+                    write_u32_be(&mut rdata, *number)?;
+                }
+                DnsRecord::write_rdata(rdata.readable(), out)
+            }
+            DnsRecordData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                let mut rdata: FixedBuf<512> = FixedBuf::new();
+                for number in [priority, weight, port] {
+                    write_u16_be(&mut rdata, *number)?;
+                }
+                // > Unless and until permitted by future standards action, name compression is
+                // > not to be used for this field.
+                // https://datatracker.ietf.org/doc/html/rfc2782
+                target.write(&mut rdata)?;
+                DnsRecord::write_rdata(rdata.readable(), out)
+            }
+            DnsRecordData::TXT(strings) => DnsRecord::write_rdata(
+                &Self::txt_rdata(strings).ok_or(DnsError::InvalidRdata)?,
+                out,
+            ),
+            DnsRecordData::Unknown(_, rdata) => DnsRecord::write_rdata(rdata, out),
+        }
+    }
+}
+/// Writes the RDATA in presentation form.  Names are written with the trailing dot, and RDATA of
+/// types we do not model in the generic format.
+impl Display for DnsRecordData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            DnsRecordData::A(addr) => write!(f, "{addr}"),
+            DnsRecordData::AAAA(addr) => write!(f, "{addr}"),
+            DnsRecordData::CNAME(target) => write!(f, "{target}."),
+            DnsRecordData::MX {
+                preference,
+                exchange,
+            } => write!(f, "{preference} {exchange}."),
+            DnsRecordData::NS(name_server) => write!(f, "{name_server}."),
+            DnsRecordData::PTR(target) => write!(f, "{target}."),
+            DnsRecordData::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => write!(
+                f,
+                "{mname}. {rname}. {serial} {refresh} {retry} {expire} {minimum}"
+            ),
+            DnsRecordData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(f, "{priority} {weight} {port} {target}."),
+            // Without strings there is no presentation form.
+            DnsRecordData::TXT(strings) if strings.is_empty() => {
+                write!(f, "{}", Self::format_generic_rdata(&[]))
+            }
+            DnsRecordData::TXT(strings) => {
+                for (n, string) in strings.iter().enumerate() {
+                    if n != 0 {
+                        write!(f, " ")?;
+                    }
+                    Self::fmt_character_string(f, string)?;
+                }
+                Ok(())
+            }
+            DnsRecordData::Unknown(_, rdata) => {
+                write!(f, "{}", Self::format_generic_rdata(rdata))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsRecordData {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=9)? {
+            0 => DnsRecordData::A(u.arbitrary()?),
+            1 => DnsRecordData::AAAA(u.arbitrary()?),
+            2 => DnsRecordData::CNAME(u.arbitrary()?),
+            3 => DnsRecordData::MX {
+                preference: u.arbitrary()?,
+                exchange: u.arbitrary()?,
+            },
+            4 => DnsRecordData::NS(u.arbitrary()?),
+            5 => DnsRecordData::SOA {
+                mname: u.arbitrary()?,
+                rname: u.arbitrary()?,
+                serial: u.arbitrary()?,
+                refresh: u.arbitrary()?,
+                retry: u.arbitrary()?,
+                expire: u.arbitrary()?,
+                minimum: u.arbitrary()?,
+            },
+            6 => {
+                let strings: Vec<&[u8]> = u.arbitrary()?;
+                DnsRecordData::TXT(
+                    strings
+                        .into_iter()
+                        .map(|string| string[..string.len().min(255)].to_vec())
+                        .collect(),
+                )
+            }
+            7 => DnsRecordData::PTR(u.arbitrary()?),
+            8 => DnsRecordData::SRV {
+                priority: u.arbitrary()?,
+                weight: u.arbitrary()?,
+                port: u.arbitrary()?,
+                target: u.arbitrary()?,
+            },
+            _ => {
+                // Reading turns types we model into their own variants.  Use a private-use type.
+                let typ = match DnsType::new(u.arbitrary()?) {
+                    DnsType::Unknown(value) => DnsType::Unknown(value),
+                    other => DnsType::Unknown(0xFF00 | (other.num() & 0xFF)),
+                };
+                DnsRecordData::Unknown(typ, u.arbitrary()?)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_generic() {
+    assert_eq!(
+        Ok(DnsRecordData::Unknown(
+            DnsType::Unknown(65534),
+            vec![0xAB, 0xCD, 0xEF]
+        )),
+        DnsRecordData::parse_generic(&DnsType::Unknown(65534), "\\# 3 abcdef")
+    );
+    assert_eq!(
+        Ok(DnsRecordData::Unknown(
+            DnsType::Unknown(65534),
+            vec![0xAB, 0xCD, 0xEF]
+        )),
+        DnsRecordData::parse_generic(&DnsType::Unknown(65534), "\\# 3 AB CDEF")
+    );
+    assert_eq!(
+        Ok(DnsRecordData::Unknown(DnsType::Unknown(65534), Vec::new())),
+        DnsRecordData::parse_generic(&DnsType::Unknown(65534), "\\# 0")
+    );
+    assert_eq!(
+        Ok(DnsRecordData::A(Ipv4Addr::new(10, 0, 0, 1))),
+        DnsRecordData::parse_generic(&DnsType::A, "\\# 4 0A000001")
+    );
+    DnsRecordData::parse_generic(&DnsType::A, "\\# 3 0A0000").unwrap_err();
+    DnsRecordData::parse_generic(&DnsType::TXT, "# 1 00").unwrap_err();
+    DnsRecordData::parse_generic(&DnsType::TXT, "\\# 2 00").unwrap_err();
+    DnsRecordData::parse_generic(&DnsType::TXT, "\\# 1 000").unwrap_err();
+    DnsRecordData::parse_generic(&DnsType::TXT, "\\# 1 0g").unwrap_err();
+    DnsRecordData::parse_generic(&DnsType::TXT, "\\# x").unwrap_err();
+    DnsRecordData::parse_generic(&DnsType::TXT, "\\# 65536").unwrap_err();
+    assert_eq!("\\# 0", DnsRecordData::format_generic_rdata(&[]));
+    assert_eq!(
+        "\\# 3 ABCDEF",
+        DnsRecordData::format_generic_rdata(&[0xAB, 0xCD, 0xEF])
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_decompresses() {
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    // a.b, then MX RDATA with preference 10 and exchange c.b
+    buf.write_bytes(&[1, b'a', 1, b'b', 0, 0, 10, 1, b'c', 0xC0, 2])
+        .unwrap();
+    buf.read_bytes(5);
+    assert_eq!(
+        Ok(DnsRecordData::MX {
+            preference: 10,
+            exchange: DnsName::new("c.b").unwrap(),
+        }),
+        DnsRecordData::read(&DnsType::MX, 6, &mut buf)
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_parse() {
+    for (typ, text) in [
+        (DnsType::A, "10.0.0.1"),
+        (DnsType::AAAA, "2001:db8::1"),
+        (DnsType::CNAME, "c.d."),
+        (DnsType::CNAME, "."),
+        (DnsType::MX, "10 mail.c.d."),
+        (DnsType::MX, "0 ."),
+        (DnsType::NS, "ns1.c.d."),
+        (DnsType::PTR, "host.c.d."),
+        (
+            DnsType::SOA,
+            "ns1.c.d. admin.c.d. 2024010101 7200 3600 1209600 300",
+        ),
+        (DnsType::SRV, "10 60 5060 sip.c.d."),
+        (DnsType::SRV, "0 0 0 ."),
+        (DnsType::TXT, "\"hi\""),
+        (
+            DnsType::TXT,
+            "\"v=spf1  -all\" \"\" \"a\\\"b\\\\c\\009\\255\"",
+        ),
+        (DnsType::TXT, "\\# 0"),
+        (DnsType::Unknown(65534), "\\# 0"),
+    ] {
+        let data = DnsRecordData::parse(&typ, text).unwrap();
+        assert_eq!(typ, data.typ());
+        assert_eq!(text, data.to_string());
+    }
+    assert_eq!(
+        Ok(DnsRecordData::CNAME(DnsName::new("c.d").unwrap())),
+        DnsRecordData::parse(&DnsType::CNAME, "c.d")
+    );
+    DnsRecordData::parse(&DnsType::A, "::1").unwrap_err();
+    DnsRecordData::parse(&DnsType::AAAA, "10.0.0.1").unwrap_err();
+    assert_eq!(
+        Ok(DnsRecordData::TXT(vec![
+            b"hi".to_vec(),
+            b"a\"b c".to_vec(),
+            vec![9]
+        ])),
+        DnsRecordData::parse(&DnsType::TXT, "hi \"a\\\"b c\" \\009")
+    );
+    DnsRecordData::parse(&DnsType::TXT, "").unwrap_err();
+    DnsRecordData::parse(&DnsType::TXT, "\"hi").unwrap_err();
+    DnsRecordData::parse(&DnsType::TXT, "a\"b\"").unwrap_err();
+    DnsRecordData::parse(&DnsType::TXT, "\\256").unwrap_err();
+    DnsRecordData::parse(&DnsType::TXT, &"a".repeat(256)).unwrap_err();
+    DnsRecordData::parse(&DnsType::MX, "10").unwrap_err();
+    DnsRecordData::parse(&DnsType::MX, "-1 c.d").unwrap_err();
+    DnsRecordData::parse(&DnsType::MX, "10 c.d e.f").unwrap_err();
+    DnsRecordData::parse(&DnsType::SOA, "ns1.c.d admin.c.d 1 2 3 4").unwrap_err();
+    DnsRecordData::parse(&DnsType::SOA, "ns1.c.d admin.c.d 1 2 3 4 5 6").unwrap_err();
+    DnsRecordData::parse(&DnsType::SOA, "ns1.c.d admin.c.d 1 2 3 4 x").unwrap_err();
+    DnsRecordData::parse(&DnsType::SRV, "10 60 5060").unwrap_err();
+    DnsRecordData::parse(&DnsType::SRV, "10 60 65536 sip.c.d").unwrap_err();
+    DnsRecordData::parse(&DnsType::SRV, "10 60 5060 sip.c.d e.f").unwrap_err();
+}
+
+#[cfg(test)]
+#[test]
+fn test_txt() {
+    assert_eq!(
+        DnsRecordData::TXT(vec![Vec::new()]),
+        DnsRecordData::txt(b"")
+    );
+    let DnsRecordData::TXT(strings) = DnsRecordData::txt(&[b'a'; 600]) else {
+        unreachable!()
+    };
+    assert_eq!(
+        vec![255, 255, 90],
+        strings.iter().map(Vec::len).collect::<Vec<usize>>()
+    );
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[2, b'h', b'i', 0, 1, b'x']).unwrap();
+    assert_eq!(
+        Ok(DnsRecordData::TXT(vec![
+            b"hi".to_vec(),
+            Vec::new(),
+            b"x".to_vec()
+        ])),
+        DnsRecordData::read(&DnsType::TXT, 6, &mut buf)
+    );
+    // A length runs past the end of the RDATA.
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[3, b'h', b'i', 0]).unwrap();
+    assert_eq!(
+        Err(DnsError::InvalidRdata),
+        DnsRecordData::read(&DnsType::TXT, 3, &mut buf)
+    );
+    assert_eq!(
+        Ok(DnsRecordData::TXT(Vec::new())),
+        DnsRecordData::parse_generic(&DnsType::TXT, "\\# 0")
+    );
+    let mut out: FixedBuf<512> = FixedBuf::new();
+    assert_eq!(
+        Err(DnsError::InvalidRdata),
+        DnsRecordData::TXT(vec![vec![0; 256]])
+            .write_compressed(&mut out, &mut DnsNameCompressor::disabled())
+    );
+}
@@ -0,0 +1,106 @@
+use crate::{DnsClass, DnsError, DnsNameRef, DnsRecord, DnsRecordData, DnsType};
+
+/// A resource record inside a message, borrowed in its wire form.  See [`DnsRecord`].
+#[derive(Clone, Debug)]
+pub struct DnsRecordRef<'a> {
+    message: &'a [u8],
+    name: DnsNameRef<'a>,
+    typ: DnsType,
+    class: DnsClass,
+    ttl: u32,
+    rdata_offset: usize,
+    rdata_len: usize,
+}
+impl<'a> DnsRecordRef<'a> {
+    /// Reads the resource record at `offset` in `message`.  Returns the record and the offset of
+    /// the byte after it.
+    ///
+    /// This checks only the owner name and that the RDATA fits in the message, so it accepts
+    /// pseudo-records like OPT.  [`DnsRecordRef::to_record`] checks the rest.
+    ///
+    /// # Errors
+    /// Returns an error when `message` does not contain a resource record at `offset`.
+    pub fn read(message: &'a [u8], offset: usize) -> Result<(Self, usize), DnsError> {
+        let (name, offset) = DnsNameRef::read(message, offset)?;
+        let fixed = message
+            .get(offset..offset + 10)
+            .ok_or(DnsError::Truncated)?;
+        let typ = DnsType::new(u16::from_be_bytes([fixed[0], fixed[1]]));
+        let class = DnsClass::new(u16::from_be_bytes([fixed[2], fixed[3]]));
+        let ttl = u32::from_be_bytes([fixed[4], fixed[5], fixed[6], fixed[7]]);
+        let rdata_len = usize::from(u16::from_be_bytes([fixed[8], fixed[9]]));
+        let rdata_offset = offset + 10;
+        if message.len() < rdata_offset + rdata_len {
+            return Err(DnsError::Truncated);
+        }
+        Ok((
+            Self {
+                message,
+                name,
+                typ,
+                class,
+                ttl,
+                rdata_offset,
+                rdata_len,
+            },
+            rdata_offset + rdata_len,
+        ))
+    }
+
+    #[must_use]
+    pub fn name(&self) -> DnsNameRef<'a> {
+        self.name
+    }
+
+    #[must_use]
+    pub fn typ(&self) -> DnsType {
+        self.typ.clone()
+    }
+
+    #[must_use]
+    pub fn class(&self) -> DnsClass {
+        self.class
+    }
+
+    /// > Implementations should treat TTL values received with the most significant bit set as
+    /// > if the entire value received was zero.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2181#section-8>
+    #[must_use]
+    pub fn ttl_seconds(&self) -> u32 {
+        if self.ttl > 0x7FFF_FFFF {
+            0
+        } else {
+            self.ttl
+        }
+    }
+
+    /// The TTL field as sent.  OPT records keep flags in it.
+    pub(crate) fn raw_ttl(&self) -> u32 {
+        self.ttl
+    }
+
+    /// Returns the raw RDATA.  Names in it may contain compression pointers.
+    #[must_use]
+    pub fn rdata(&self) -> &'a [u8] {
+        &self.message[self.rdata_offset..self.rdata_offset + self.rdata_len]
+    }
+
+    /// Copies the record into a [`DnsRecord`].
+    ///
+    /// # Errors
+    /// Returns an error when the record has an unsupported class or invalid RDATA.
+    pub fn to_record(&self) -> Result<DnsRecord, DnsError> {
+        if self.class != DnsClass::Internet && self.class != DnsClass::Any {
+            return Err(DnsError::InvalidClass);
+        }
+        let data =
+            DnsRecordData::read_at(&self.typ, self.message, self.rdata_offset, self.rdata_len)?;
+        Ok(DnsRecord::new(
+            self.name.to_name(),
+            self.class,
+            self.ttl_seconds(),
+            data,
+        ))
+    }
+}
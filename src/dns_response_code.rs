@@ -1,3 +1,8 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
 /// > `RCODE` Response code - this 4 bit field is set as part of responses.  The values have the
 /// > following interpretation:
 /// > - `0` No error condition
@@ -14,6 +19,12 @@
 /// > - `6-15` Reserved for future use.
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.1>
+///
+/// Later RFCs assigned the reserved values and, with EDNS, extended the code to 12 bits.  The
+/// upper 8 bits go in the OPT record, so codes above 15 only fit in messages with one.  See
+/// [`DnsMessage::response_code`](crate::DnsMessage::response_code).
+///
+/// <https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-6>
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum DnsResponseCode {
     NoError,
@@ -22,24 +33,95 @@ pub enum DnsResponseCode {
     NameError,
     NotImplemented,
     Refused,
-    Reserved(u8),
+    /// `YXDOMAIN` Name exists when it should not.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    YxDomain,
+    /// `YXRRSET` RRset exists when it should not.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    YxRrSet,
+    /// `NXRRSET` RRset that should exist does not.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    NxRrSet,
+    /// `NOTAUTH` Server not authoritative for zone, or not authorized.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    NotAuth,
+    /// `NOTZONE` Name not contained in zone.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    NotZone,
+    /// `DSOTYPENI` DSO-TYPE not implemented.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc8490#section-10.2>
+    DsoTypeNotImplemented,
+    /// `BADVERS` Bad OPT version.  TSIG uses the same value for `BADSIG`, signature failure.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc6891#section-9>
+    BadVersion,
+    /// `BADKEY` Key not recognized.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc8945#section-5.3.2>
+    BadKey,
+    /// `BADTIME` Signature out of time window.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc8945#section-5.3.2>
+    BadTime,
+    /// `BADMODE` Bad TKEY mode.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2930#section-2.6>
+    BadMode,
+    /// `BADNAME` Duplicate key name.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2930#section-2.6>
+    BadName,
+    /// `BADALG` Algorithm not supported.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc2930#section-2.6>
+    BadAlgorithm,
+    /// `BADTRUNC` Bad truncation.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc8945#section-5.3.2>
+    BadTruncation,
+    /// `BADCOOKIE` Bad or missing server cookie.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc7873#section-8>
+    BadCookie,
+    Reserved(u16),
 }
 impl DnsResponseCode {
+    /// Makes a response code from the lower 12 bits of `value`.
     #[must_use]
-    pub fn new(value: u8) -> Self {
-        match value {
+    pub fn new(value: u16) -> Self {
+        match value & 0xFFF {
             0 => DnsResponseCode::NoError,
             1 => DnsResponseCode::FormatError,
             2 => DnsResponseCode::ServerFailure,
             3 => DnsResponseCode::NameError,
             4 => DnsResponseCode::NotImplemented,
             5 => DnsResponseCode::Refused,
+            6 => DnsResponseCode::YxDomain,
+            7 => DnsResponseCode::YxRrSet,
+            8 => DnsResponseCode::NxRrSet,
+            9 => DnsResponseCode::NotAuth,
+            10 => DnsResponseCode::NotZone,
+            11 => DnsResponseCode::DsoTypeNotImplemented,
+            16 => DnsResponseCode::BadVersion,
+            17 => DnsResponseCode::BadKey,
+            18 => DnsResponseCode::BadTime,
+            19 => DnsResponseCode::BadMode,
+            20 => DnsResponseCode::BadName,
+            21 => DnsResponseCode::BadAlgorithm,
+            22 => DnsResponseCode::BadTruncation,
+            23 => DnsResponseCode::BadCookie,
             other => DnsResponseCode::Reserved(other),
         }
     }
 
     #[must_use]
-    pub fn num(&self) -> u8 {
+    pub fn num(&self) -> u16 {
         match self {
             DnsResponseCode::NoError => 0,
             DnsResponseCode::FormatError => 1,
@@ -47,7 +129,138 @@ impl DnsResponseCode {
             DnsResponseCode::NameError => 3,
             DnsResponseCode::NotImplemented => 4,
             DnsResponseCode::Refused => 5,
+            DnsResponseCode::YxDomain => 6,
+            DnsResponseCode::YxRrSet => 7,
+            DnsResponseCode::NxRrSet => 8,
+            DnsResponseCode::NotAuth => 9,
+            DnsResponseCode::NotZone => 10,
+            DnsResponseCode::DsoTypeNotImplemented => 11,
+            DnsResponseCode::BadVersion => 16,
+            DnsResponseCode::BadKey => 17,
+            DnsResponseCode::BadTime => 18,
+            DnsResponseCode::BadMode => 19,
+            DnsResponseCode::BadName => 20,
+            DnsResponseCode::BadAlgorithm => 21,
+            DnsResponseCode::BadTruncation => 22,
+            DnsResponseCode::BadCookie => 23,
             DnsResponseCode::Reserved(other) => *other,
         }
     }
+
+    /// Returns the lower 4 bits, which go in the message header.
+    #[must_use]
+    pub fn header_bits(&self) -> u8 {
+        self.num().to_be_bytes()[1] & 0xF
+    }
+
+    /// Returns the upper 8 bits, which go in the OPT record's `EXTENDED-RCODE` field.
+    #[must_use]
+    pub fn extended_bits(&self) -> u8 {
+        // The code has 12 bits, so this fits.
+        (self.num() >> 4).to_be_bytes()[1]
+    }
+}
+impl Display for DnsResponseCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            DnsResponseCode::NoError => write!(f, "NOERROR"),
+            DnsResponseCode::FormatError => write!(f, "FORMERR"),
+            DnsResponseCode::ServerFailure => write!(f, "SERVFAIL"),
+            DnsResponseCode::NameError => write!(f, "NXDOMAIN"),
+            DnsResponseCode::NotImplemented => write!(f, "NOTIMP"),
+            DnsResponseCode::Refused => write!(f, "REFUSED"),
+            DnsResponseCode::YxDomain => write!(f, "YXDOMAIN"),
+            DnsResponseCode::YxRrSet => write!(f, "YXRRSET"),
+            DnsResponseCode::NxRrSet => write!(f, "NXRRSET"),
+            DnsResponseCode::NotAuth => write!(f, "NOTAUTH"),
+            DnsResponseCode::NotZone => write!(f, "NOTZONE"),
+            DnsResponseCode::DsoTypeNotImplemented => write!(f, "DSOTYPENI"),
+            DnsResponseCode::BadVersion => write!(f, "BADVERS"),
+            DnsResponseCode::BadKey => write!(f, "BADKEY"),
+            DnsResponseCode::BadTime => write!(f, "BADTIME"),
+            DnsResponseCode::BadMode => write!(f, "BADMODE"),
+            DnsResponseCode::BadName => write!(f, "BADNAME"),
+            DnsResponseCode::BadAlgorithm => write!(f, "BADALG"),
+            DnsResponseCode::BadTruncation => write!(f, "BADTRUNC"),
+            DnsResponseCode::BadCookie => write!(f, "BADCOOKIE"),
+            DnsResponseCode::Reserved(n) => write!(f, "RESERVED{n}"),
+        }
+    }
+}
+impl FromStr for DnsResponseCode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let upper = value.to_ascii_uppercase();
+        match upper.as_str() {
+            "NOERROR" => Ok(DnsResponseCode::NoError),
+            "FORMERR" => Ok(DnsResponseCode::FormatError),
+            "SERVFAIL" => Ok(DnsResponseCode::ServerFailure),
+            "NXDOMAIN" => Ok(DnsResponseCode::NameError),
+            "NOTIMP" => Ok(DnsResponseCode::NotImplemented),
+            "REFUSED" => Ok(DnsResponseCode::Refused),
+            "YXDOMAIN" => Ok(DnsResponseCode::YxDomain),
+            "YXRRSET" => Ok(DnsResponseCode::YxRrSet),
+            "NXRRSET" => Ok(DnsResponseCode::NxRrSet),
+            "NOTAUTH" => Ok(DnsResponseCode::NotAuth),
+            "NOTZONE" => Ok(DnsResponseCode::NotZone),
+            "DSOTYPENI" => Ok(DnsResponseCode::DsoTypeNotImplemented),
+            "BADVERS" | "BADSIG" => Ok(DnsResponseCode::BadVersion),
+            "BADKEY" => Ok(DnsResponseCode::BadKey),
+            "BADTIME" => Ok(DnsResponseCode::BadTime),
+            "BADMODE" => Ok(DnsResponseCode::BadMode),
+            "BADNAME" => Ok(DnsResponseCode::BadName),
+            "BADALG" => Ok(DnsResponseCode::BadAlgorithm),
+            "BADTRUNC" => Ok(DnsResponseCode::BadTruncation),
+            "BADCOOKIE" => Ok(DnsResponseCode::BadCookie),
+            other => other
+                .strip_prefix("RESERVED")
+                .and_then(|n| n.parse::<u16>().ok())
+                .filter(|n| *n < 4096)
+                .map(DnsResponseCode::new)
+                .ok_or_else(|| format!("not a DNS response code: {value:?}")),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsResponseCode {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_new_num() {
+    for n in 0..4096 {
+        assert_eq!(n, DnsResponseCode::new(n).num());
+    }
+    assert_eq!(DnsResponseCode::BadVersion, DnsResponseCode::new(16));
+    assert_eq!(DnsResponseCode::Reserved(12), DnsResponseCode::new(12));
+    assert_eq!(0, DnsResponseCode::BadVersion.header_bits());
+    assert_eq!(1, DnsResponseCode::BadVersion.extended_bits());
+    assert_eq!(7, DnsResponseCode::BadCookie.header_bits());
+    assert_eq!(1, DnsResponseCode::BadCookie.extended_bits());
+    assert_eq!(0xF, DnsResponseCode::Reserved(4095).header_bits());
+    assert_eq!(0xFF, DnsResponseCode::Reserved(4095).extended_bits());
+    assert_eq!(0, DnsResponseCode::NameError.extended_bits());
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    for response_code in [
+        DnsResponseCode::NoError,
+        DnsResponseCode::NameError,
+        DnsResponseCode::Reserved(12),
+    ] {
+        assert_eq!(Ok(response_code), response_code.to_string().parse());
+    }
+    assert_eq!("SERVFAIL", DnsResponseCode::ServerFailure.to_string());
+    assert_eq!(Ok(DnsResponseCode::Refused), "refused".parse());
+    assert_eq!(Ok(DnsResponseCode::BadVersion), "BADSIG".parse());
+    assert_eq!("BADCOOKIE", DnsResponseCode::BadCookie.to_string());
+    "BADX".parse::<DnsResponseCode>().unwrap_err();
+    "RESERVED4096".parse::<DnsResponseCode>().unwrap_err();
 }
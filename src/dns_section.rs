@@ -0,0 +1,39 @@
+use core::fmt::{Display, Formatter};
+
+/// A part of a message.
+///
+/// > ```text
+/// >     +---------------------+
+/// >     |        Header       |
+/// >     +---------------------+
+/// >     |       Question      | the question for the name server
+/// >     +---------------------+
+/// >     |        Answer       | RRs answering the question
+/// >     +---------------------+
+/// >     |      Authority      | RRs pointing toward an authority
+/// >     +---------------------+
+/// >     |      Additional     | RRs holding additional information
+/// >     +---------------------+
+/// > ```
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1>
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DnsSection {
+    Header,
+    Question,
+    Answer,
+    Authority,
+    Additional,
+}
+impl Display for DnsSection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            DnsSection::Header => write!(f, "header"),
+            DnsSection::Question => write!(f, "question"),
+            DnsSection::Answer => write!(f, "answer"),
+            DnsSection::Authority => write!(f, "authority"),
+            DnsSection::Additional => write!(f, "additional"),
+        }
+    }
+}
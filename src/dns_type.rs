@@ -1,6 +1,8 @@
-use crate::{read_u16_be, write_u16_be, DnsError};
+use crate::{read_u16_be, write_u16_be, DnsError, FixedBuf};
+use alloc::format;
+use alloc::string::String;
 use core::fmt::{Display, Formatter};
-use fixed_buffer::FixedBuf;
+use core::str::FromStr;
 
 /// > TYPE fields are used in resource records.  Note that these types are a subset of QTYPEs.
 ///
@@ -31,8 +33,12 @@ pub enum DnsType {
     PTR,
     /// Marks the start of a zone of authority
     SOA,
+    /// Service locator
+    SRV,
     /// Text string
     TXT,
+    /// EDNS(0) pseudo-record, see [`DnsEdns`](crate::DnsEdns)
+    OPT,
     ANY,
     Unknown(u16),
 }
@@ -47,7 +53,9 @@ impl DnsType {
             2 => DnsType::NS,
             12 => DnsType::PTR,
             6 => DnsType::SOA,
+            33 => DnsType::SRV,
             16 => DnsType::TXT,
+            41 => DnsType::OPT,
             255 => DnsType::ANY,
             other => DnsType::Unknown(other),
         }
@@ -63,7 +71,9 @@ impl DnsType {
             DnsType::NS => 2,
             DnsType::PTR => 12,
             DnsType::SOA => 6,
+            DnsType::SRV => 33,
             DnsType::TXT => 16,
+            DnsType::OPT => 41,
             DnsType::ANY => 255,
             DnsType::Unknown(other) => *other,
         }
@@ -81,6 +91,12 @@ impl DnsType {
         write_u16_be(out, self.num())
     }
 }
+/// Writes the type mnemonic, or `TYPE` and the number for types without one.
+///
+/// > The type field for an unknown RR type is represented by the word "TYPE" immediately
+/// > followed by the decimal RR type number, with no intervening whitespace.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc3597#section-5>
 impl Display for DnsType {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
         match self {
@@ -91,9 +107,66 @@ impl Display for DnsType {
             DnsType::NS => write!(f, "NS"),
             DnsType::PTR => write!(f, "PTR"),
             DnsType::SOA => write!(f, "SOA"),
+            DnsType::SRV => write!(f, "SRV"),
             DnsType::TXT => write!(f, "TXT"),
+            DnsType::OPT => write!(f, "OPT"),
             DnsType::ANY => write!(f, "ANY"),
-            DnsType::Unknown(n) => write!(f, "Unknown({n})"),
+            DnsType::Unknown(n) => write!(f, "TYPE{n}"),
         }
     }
 }
+impl FromStr for DnsType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let upper = value.to_ascii_uppercase();
+        let typ = match upper.as_str() {
+            "A" => DnsType::A,
+            "AAAA" => DnsType::AAAA,
+            "CNAME" => DnsType::CNAME,
+            "MX" => DnsType::MX,
+            "NS" => DnsType::NS,
+            "PTR" => DnsType::PTR,
+            "SOA" => DnsType::SOA,
+            "SRV" => DnsType::SRV,
+            "TXT" => DnsType::TXT,
+            "OPT" => DnsType::OPT,
+            "ANY" => DnsType::ANY,
+            other => other
+                .strip_prefix("TYPE")
+                .and_then(|n| n.parse().ok())
+                .map(DnsType::new)
+                .ok_or_else(|| format!("not a DNS type: {value:?}"))?,
+        };
+        Ok(typ)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DnsType {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_display_from_str() {
+    for typ in [
+        DnsType::A,
+        DnsType::CNAME,
+        DnsType::SRV,
+        DnsType::ANY,
+        DnsType::Unknown(65534),
+    ] {
+        assert_eq!(Ok(typ.clone()), typ.to_string().parse());
+    }
+    assert_eq!("TYPE65534", DnsType::Unknown(65534).to_string());
+    assert_eq!(Ok(DnsType::AAAA), "aaaa".parse());
+    assert_eq!(Ok(DnsType::A), "TYPE1".parse());
+    assert_eq!(
+        <Result<DnsType, String>>::Err("not a DNS type: \"TYPE\"".to_string()),
+        "TYPE".parse()
+    );
+    "TYPE65536".parse::<DnsType>().unwrap_err();
+}
@@ -0,0 +1,141 @@
+use core::fmt::{Debug, Formatter};
+
+/// The error returned by [`FixedBuf::write_bytes`] when the data does not fit.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NotEnoughSpaceError {}
+
+/// A fixed-size byte buffer with separate read and write positions.
+///
+/// With the `std` feature, the codec reads and writes `fixed_buffer::FixedBuf`.  Without it,
+/// the codec uses this type, which has the same methods and behavior for the parts the codec
+/// needs.  Like the original, it moves both positions back to the start when all of its data has
+/// been read.
+#[derive(Copy, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FixedBuf<const SIZE: usize> {
+    mem: [u8; SIZE],
+    read_index: usize,
+    write_index: usize,
+}
+impl<const SIZE: usize> FixedBuf<SIZE> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            mem: [0_u8; SIZE],
+            read_index: 0,
+            write_index: 0,
+        }
+    }
+
+    /// Returns the number of unread bytes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.write_index - self.read_index
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.write_index == self.read_index
+    }
+
+    /// Returns the whole backing memory, including bytes already read and bytes not yet written.
+    #[must_use]
+    pub fn mem(&self) -> &[u8] {
+        &self.mem
+    }
+
+    /// Returns the unread bytes.
+    #[must_use]
+    pub fn readable(&self) -> &[u8] {
+        &self.mem[self.read_index..self.write_index]
+    }
+
+    /// Reads `num_bytes` bytes.
+    ///
+    /// # Panics
+    /// Panics when the buffer holds fewer than `num_bytes` unread bytes.
+    pub fn read_bytes(&mut self, num_bytes: usize) -> &[u8] {
+        let new_read_index = self.read_index + num_bytes;
+        assert!(new_read_index <= self.write_index, "read would underflow");
+        let old_read_index = self.read_index;
+        self.read_index = new_read_index;
+        if self.read_index == self.write_index {
+            self.read_index = 0;
+            self.write_index = 0;
+        }
+        &self.mem[old_read_index..new_read_index]
+    }
+
+    /// Reads `num_bytes` bytes, or returns `None` when the buffer holds fewer than that.
+    pub fn try_read_bytes(&mut self, num_bytes: usize) -> Option<&[u8]> {
+        if self.len() < num_bytes {
+            None
+        } else {
+            Some(self.read_bytes(num_bytes))
+        }
+    }
+
+    /// Fills `dest` with unread bytes, or returns `None` when the buffer holds too few.
+    pub fn try_read_exact(&mut self, dest: &mut [u8]) -> Option<()> {
+        let src = self.try_read_bytes(dest.len())?;
+        dest.copy_from_slice(src);
+        Some(())
+    }
+
+    /// Appends `data` and returns its length.
+    ///
+    /// # Errors
+    /// Returns an error when `data` does not fit in the unused end of the buffer.
+    pub fn write_bytes(&mut self, data: &[u8]) -> Result<usize, NotEnoughSpaceError> {
+        let writable = self.writable();
+        if writable.len() < data.len() {
+            return Err(NotEnoughSpaceError {});
+        }
+        writable[..data.len()].copy_from_slice(data);
+        self.wrote(data.len());
+        Ok(data.len())
+    }
+
+    /// Returns the unused end of the buffer.  Call [`FixedBuf::wrote`] after filling part of it.
+    pub fn writable(&mut self) -> &mut [u8] {
+        &mut self.mem[self.write_index..]
+    }
+
+    /// Marks `num_bytes` bytes of [`FixedBuf::writable`] as written.
+    ///
+    /// # Panics
+    /// Panics when that is more than the unused end of the buffer.
+    pub fn wrote(&mut self, num_bytes: usize) {
+        let new_write_index = self.write_index + num_bytes;
+        assert!(new_write_index <= SIZE, "write would overflow");
+        self.write_index = new_write_index;
+    }
+}
+impl<const SIZE: usize> Default for FixedBuf<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<const SIZE: usize> Debug for FixedBuf<SIZE> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "FixedBuf<{SIZE}>{{{:?}}}", self.readable())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_write() {
+    let mut buf: FixedBuf<4> = FixedBuf::new();
+    assert!(buf.is_empty());
+    assert_eq!(Ok(3), buf.write_bytes(&[1, 2, 3]));
+    assert_eq!(Err(NotEnoughSpaceError {}), buf.write_bytes(&[4, 5]));
+    assert_eq!(&[1, 2][..], buf.read_bytes(2));
+    assert_eq!(1, buf.len());
+    let mut dest = [0; 2];
+    assert_eq!(None, buf.try_read_exact(&mut dest));
+    assert_eq!(Some(&[3][..]), buf.try_read_bytes(1));
+    // Reading everything resets the positions.
+    assert_eq!(buf.mem().as_ptr(), buf.readable().as_ptr());
+    buf.writable()[..2].copy_from_slice(&[6, 7]);
+    buf.wrote(2);
+    assert_eq!(&[6, 7][..], buf.readable());
+}
@@ -1,28 +1,68 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![forbid(unsafe_code)]
 
+extern crate alloc;
+
 mod dns_class;
+mod dns_edns;
+mod dns_edns_option;
+#[cfg(feature = "serde")]
+mod dns_json;
 mod dns_message;
+mod dns_message_builder;
 mod dns_message_header;
+mod dns_message_ref;
 mod dns_name;
+mod dns_name_compressor;
+mod dns_name_ref;
 mod dns_op_code;
+mod dns_parse_error;
 mod dns_question;
+mod dns_question_ref;
 mod dns_record;
+mod dns_record_data;
+mod dns_record_ref;
 mod dns_response_code;
+mod dns_section;
 mod dns_type;
+#[cfg(any(not(feature = "std"), test))]
+mod fixed_buf;
+#[cfg(feature = "std")]
 mod server;
 
 pub use dns_class::DnsClass;
+pub use dns_edns::DnsEdns;
+pub use dns_edns_option::DnsEdnsOption;
 pub use dns_message::DnsMessage;
+pub use dns_message_builder::DnsMessageBuilder;
 pub use dns_message_header::DnsMessageHeader;
+pub use dns_message_ref::DnsMessageRef;
 pub use dns_name::DnsName;
+pub use dns_name_ref::DnsNameRef;
 pub use dns_op_code::DnsOpCode;
+pub use dns_parse_error::DnsParseError;
 pub use dns_question::DnsQuestion;
+pub use dns_question_ref::DnsQuestionRef;
 pub use dns_record::DnsRecord;
+pub use dns_record_data::DnsRecordData;
+pub use dns_record_ref::DnsRecordRef;
 pub use dns_response_code::DnsResponseCode;
+pub use dns_section::DnsSection;
 pub use dns_type::DnsType;
-pub use server::{process_datagram, serve_udp};
+#[cfg(not(feature = "std"))]
+pub use fixed_buf::{FixedBuf, NotEnoughSpaceError};
+#[cfg(feature = "std")]
+pub use fixed_buffer::{FixedBuf, NotEnoughSpaceError};
+#[cfg(feature = "std")]
+pub use server::{process_datagram, serve_udp, ServerConfig, Zones, MAX_UDP_PAYLOAD_SIZE};
+
+use alloc::string::String;
+use alloc::vec::Vec;
 
-use fixed_buffer::FixedBuf;
+/// Returns the offset of the next unread byte from the start of `buf`'s memory.
+fn read_offset<const N: usize>(buf: &FixedBuf<N>) -> usize {
+    buf.readable().as_ptr() as usize - buf.mem().as_ptr() as usize
+}
 
 fn read_exact<const N: usize, const M: usize>(buf: &mut FixedBuf<N>) -> Result<[u8; M], DnsError> {
     let mut result = [0_u8; M];
@@ -30,10 +70,6 @@ fn read_exact<const N: usize, const M: usize>(buf: &mut FixedBuf<N>) -> Result<[
     Ok(result)
 }
 
-fn read_u8<const N: usize>(buf: &mut FixedBuf<N>) -> Result<u8, DnsError> {
-    buf.try_read_byte().ok_or(DnsError::Truncated)
-}
-
 // fn write_u8<const N: usize>(out: &mut FixedBuf<N>, value: u8) -> Result<(), DnsError> {
 //     out.write_bytes(&[value])
 //         .map_err(|_| DnsError::ResponseBufferFull)?;
@@ -50,6 +86,29 @@ fn read_u32_be<const N: usize>(buf: &mut FixedBuf<N>) -> Result<u32, DnsError> {
     Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
 }
 
+/// Formats `bytes` as uppercase hexadecimal digits.
+fn hex_encode(bytes: &[u8]) -> String {
+    use core::fmt::Write;
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        let _ = write!(result, "{b:02X}");
+    }
+    result
+}
+
+/// Parses hexadecimal digits into bytes.  Returns `None` when `value` has an odd number of
+/// digits or a character that is not a hex digit.
+fn hex_decode(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    value
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(core::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
 fn write_bytes<const N: usize>(out: &mut FixedBuf<N>, bytes: &[u8]) -> Result<(), DnsError> {
     out.write_bytes(bytes)
         .map_err(|_| DnsError::ResponseBufferFull)?;
@@ -75,20 +134,90 @@ pub enum DnsError {
     InvalidClass,
     InvalidLabel,
     InvalidOpCode,
+    InvalidOptRecord,
+    InvalidPointer,
+    InvalidRdata,
     NameTooLong,
+    NoOptRecord,
     NoQuestion,
     NotARequest,
+    /// The name is in a zone the server serves, but does not exist.
     NotFound,
+    /// The name is not in any zone the server serves.
+    NotAuthoritative,
     ResponseBufferFull,
-    QueryHasAdditionalRecords,
-    QueryHasAnswer,
-    QueryHasNameServer,
     TooManyAdditional,
     TooManyAnswers,
-    TooManyLabels,
     TooManyNameServers,
+    TooManyOptRecords,
+    TooManyPointers,
     TooManyQuestions,
     Truncated,
     Internal(String),
     Unreachable(&'static str, u32),
 }
+impl DnsError {
+    /// Returns the response code for a request that failed with this error, or `None` when the
+    /// server should not respond.
+    #[must_use]
+    pub fn response_code(&self) -> Option<DnsResponseCode> {
+        match self {
+            // Answering a response could start a loop between two servers.
+            DnsError::NotARequest => None,
+            DnsError::InvalidLabel
+            | DnsError::InvalidOptRecord
+            | DnsError::InvalidPointer
+            | DnsError::InvalidRdata
+            | DnsError::NameTooLong
+            | DnsError::NoQuestion
+            | DnsError::TooManyOptRecords
+            | DnsError::TooManyPointers
+            | DnsError::TooManyQuestions
+            | DnsError::Truncated => Some(DnsResponseCode::FormatError),
+            DnsError::InvalidOpCode => Some(DnsResponseCode::NotImplemented),
+            DnsError::InvalidClass | DnsError::NotAuthoritative => Some(DnsResponseCode::Refused),
+            DnsError::NotFound => Some(DnsResponseCode::NameError),
+            DnsError::NoOptRecord
+            | DnsError::ResponseBufferFull
+            | DnsError::TooManyAdditional
+            | DnsError::TooManyAnswers
+            | DnsError::TooManyNameServers
+            | DnsError::Internal(_)
+            | DnsError::Unreachable(..) => Some(DnsResponseCode::ServerFailure),
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_response_code() {
+    assert_eq!(None, DnsError::NotARequest.response_code());
+    assert_eq!(
+        Some(DnsResponseCode::FormatError),
+        DnsError::Truncated.response_code()
+    );
+    assert_eq!(
+        Some(DnsResponseCode::FormatError),
+        DnsError::NoQuestion.response_code()
+    );
+    assert_eq!(
+        Some(DnsResponseCode::NotImplemented),
+        DnsError::InvalidOpCode.response_code()
+    );
+    assert_eq!(
+        Some(DnsResponseCode::Refused),
+        DnsError::NotAuthoritative.response_code()
+    );
+    assert_eq!(
+        Some(DnsResponseCode::Refused),
+        DnsError::InvalidClass.response_code()
+    );
+    assert_eq!(
+        Some(DnsResponseCode::NameError),
+        DnsError::NotFound.response_code()
+    );
+    assert_eq!(
+        Some(DnsResponseCode::ServerFailure),
+        DnsError::Internal("x".to_string()).response_code()
+    );
+}
@@ -1,61 +1,515 @@
-use crate::{DnsError, DnsMessage, DnsName, DnsOpCode, DnsRecord, DnsType};
-use fixed_buffer::FixedBuf;
+use crate::{
+    DnsEdns, DnsError, DnsMessage, DnsMessageHeader, DnsMessageRef, DnsName, DnsOpCode, DnsRecord,
+    DnsRecordData, DnsResponseCode, DnsType, FixedBuf,
+};
 use multimap::MultiMap;
 use prob_rate_limiter::ProbRateLimiter;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::io::ErrorKind;
+use std::net::IpAddr;
 use std::time::{Duration, Instant};
 
-/// # Errors
-/// Returns `Err` when the request is malformed or the server is not configured to answer the
+/// The largest UDP response the server will send, regardless of configuration.
+pub const MAX_UDP_PAYLOAD_SIZE: usize = 4096;
+
+/// > Messages carried by UDP are restricted to 512 bytes (not counting the IP
+/// > or UDP headers).
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.2.1>
+const MIN_UDP_PAYLOAD_SIZE: u16 = 512;
+
+/// Settings for [`serve_udp`] and [`process_datagram`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServerConfig {
+    /// The UDP payload size the server advertises in EDNS responses.  The server sends responses
+    /// up to the smaller of this and the client's advertised size.  Values are clamped to
+    /// 512..=[`MAX_UDP_PAYLOAD_SIZE`].
+    pub max_udp_payload_size: u16,
+    /// Answer PTR queries for names in `in-addr.arpa` and `ip6.arpa` from the A and AAAA records
+    /// with that address, so reverse lookups match the forward records.  Such a name exists for
+    /// queries of every type, so other types get empty answers.  PTR records at the name take
+    /// precedence.  Each query for a name in those domains scans every record.
+    pub synthesize_ptr: bool,
+}
+impl ServerConfig {
+    fn udp_payload_size(&self) -> u16 {
+        #[allow(clippy::cast_possible_truncation)]
+        self.max_udp_payload_size
+            .clamp(MIN_UDP_PAYLOAD_SIZE, MAX_UDP_PAYLOAD_SIZE as u16)
+    }
+
+    /// Returns the largest response the server may send over UDP to a request with
+    /// `request_edns`.
+    #[must_use]
+    pub fn max_udp_response_len(&self, request_edns: Option<&DnsEdns>) -> usize {
+        match request_edns {
+            Some(edns) => usize::from(edns.max_payload_size().min(self.udp_payload_size())),
+            None => usize::from(MIN_UDP_PAYLOAD_SIZE),
+        }
+    }
+}
+impl Default for ServerConfig {
+    fn default() -> Self {
+        // https://www.dnsflagday.net/2020/
+        Self {
+            max_udp_payload_size: 1232,
+            synthesize_ptr: false,
+        }
+    }
+}
+
+/// The records a server answers from, indexed by name.  Build it once and use it for every
 /// request.
+#[derive(Clone, Debug)]
+pub struct Zones<'r> {
+    name_to_records: MultiMap<&'r DnsName, &'r DnsRecord>,
+    /// Names in our zones with no records of their own but with records below them.
+    empty_non_terminals: HashSet<DnsName>,
+}
+impl<'r> Zones<'r> {
+    /// A zone starts at a name with an SOA record.  Records with no SOA record above them are in a
+    /// zone that starts at the parent of the topmost of them.  So with only `aaa.example.com`, we
+    /// serve `example.com` and a query for `bbb.example.com` gets NXDOMAIN, not REFUSED.
+    #[must_use]
+    pub fn new(records: &'r [DnsRecord]) -> Self {
+        let name_to_records: MultiMap<&DnsName, &DnsRecord> =
+            records.iter().map(|x| (x.name(), x)).collect();
+        let has_soa = |name: &DnsName| {
+            name_to_records
+                .get_vec(name)
+                .into_iter()
+                .flatten()
+                .any(|record| record.typ() == DnsType::SOA)
+        };
+        let mut empty_non_terminals = HashSet::new();
+        for owner in name_to_records.keys() {
+            let mut ancestors = Vec::new();
+            let mut name = owner.parent();
+            while let Some(ancestor) = name {
+                name = ancestor.parent();
+                ancestors.push(ancestor);
+            }
+            // The ancestors below the zone apex, and the apex when it is only the parent of our
+            // topmost name, exist because `owner` does.
+            let zone_len = match ancestors.iter().position(&has_soa) {
+                Some(apex) => apex,
+                None if has_soa(owner) => 0,
+                None => {
+                    ancestors
+                        .iter()
+                        .rposition(|ancestor| name_to_records.contains_key(ancestor))
+                        .map_or(0, |topmost| topmost + 1)
+                        + 1
+                }
+            };
+            empty_non_terminals.extend(
+                ancestors
+                    .into_iter()
+                    .take(zone_len)
+                    .filter(|ancestor| !name_to_records.contains_key(ancestor)),
+            );
+        }
+        Self {
+            name_to_records,
+            empty_non_terminals,
+        }
+    }
+}
+
+/// Makes the response to `request`.  When the request cannot be answered, the response has the
+/// error's [`DnsError::response_code`].
+///
+/// # Errors
+/// Returns `Err` when the server should not respond, like when `request` is itself a response.
 pub fn process_request(
-    name_to_records: &MultiMap<&DnsName, &DnsRecord>,
-    request: &DnsMessage,
+    config: &ServerConfig,
+    zones: &Zones<'_>,
+    request: &DnsMessageRef<'_>,
 ) -> Result<DnsMessage, DnsError> {
-    if request.header.is_response {
+    if request.header().is_response {
         return Err(DnsError::NotARequest);
     }
-    if request.header.op_code != DnsOpCode::Query {
-        return Err(DnsError::InvalidOpCode);
+    let request_edns = request.edns();
+    let result = if request.header().op_code != DnsOpCode::Query {
+        Err(DnsError::InvalidOpCode)
+    } else if request_edns.as_ref().is_some_and(|edns| edns.version > 0) {
+        return bad_version_response(config, request);
+    } else {
+        answer_request(config, zones, request)
+    };
+    let mut response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            let response_code = e.response_code().ok_or(e)?;
+            let mut response = request.error_response(response_code)?;
+            // > `AA` Authoritative Answer - this bit is valid in responses, and specifies that
+            // > the responding name server is an authority for the domain name in question
+            // > section.
+            // https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.1
+            response.header.authoritative_answer = response_code == DnsResponseCode::NameError;
+            if response_code == DnsResponseCode::NameError {
+                add_negative_soa(&zones.name_to_records, &mut response);
+            }
+            response
+        }
+    };
+    if let Some(request_edns) = &request_edns {
+        let mut edns = DnsEdns::new(config.udp_payload_size());
+        // > The DO bit of the query MUST be copied in the response.
+        // https://datatracker.ietf.org/doc/html/rfc3225#section-3
+        edns.dnssec_ok = request_edns.dnssec_ok;
+        response.edns = Some(edns);
     }
+    Ok(response)
+}
+
+/// Answers a request with an EDNS version we do not implement.
+///
+/// > If a responder does not implement the VERSION level of the request, then it MUST respond
+/// > with RCODE=BADVERS.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3>
+fn bad_version_response(
+    config: &ServerConfig,
+    request: &DnsMessageRef<'_>,
+) -> Result<DnsMessage, DnsError> {
+    let mut response = request.error_response(DnsResponseCode::NoError)?;
+    response.edns = Some(DnsEdns::new(config.udp_payload_size()));
+    response.set_response_code(DnsResponseCode::BadVersion)?;
+    Ok(response)
+}
+
+/// Answers a request that [`DnsMessageRef::new`] rejected with `error`, echoing only its header.
+///
+/// # Errors
+/// Returns `error` when the request has no header or the server should not respond to it.
+fn malformed_request_response(bytes: &[u8], error: DnsError) -> Result<DnsMessage, DnsError> {
+    let Some(header) = bytes
+        .get(..12)
+        .and_then(|header| <[u8; 12]>::try_from(header).ok())
+    else {
+        return Err(error);
+    };
+    let header = DnsMessageHeader::from_bytes(header);
+    if header.is_response {
+        return Err(error);
+    }
+    let response_code = error.response_code().ok_or(error)?;
+    let mut response = DnsMessage::response(&header, Vec::new(), response_code, Vec::new())?;
+    response.header.authoritative_answer = false;
+    Ok(response)
+}
+
+fn answer_request(
+    config: &ServerConfig,
+    zones: &Zones<'_>,
+    request: &DnsMessageRef<'_>,
+) -> Result<DnsMessage, DnsError> {
+    let name_to_records = &zones.name_to_records;
     // NOTE: We only answer the first question.
-    let question = request.questions.first().ok_or(DnsError::NoQuestion)?;
+    let question = request.questions().next().ok_or(DnsError::NoQuestion)?;
     // u16::try_from(self.questions.len()).map_err(|_| ProcessError::TooManyQuestions)?,
-    let records = name_to_records
-        .get_vec(&question.name)
-        .ok_or(DnsError::NotFound)?;
-    if question.typ == DnsType::ANY {
-        request.answer_response(records.iter().copied())
+    // Reading the request does not allocate, but answering it copies the question name once.
+    // The map hashes owned `DnsName`s, so a lookup needs one, and the response echoes the
+    // question, so it needs its own copy anyway.  Matching owners with `DnsNameRef::eq_name`
+    // would save this copy only by comparing the name with every owner in the map.
+    // Keeps the client's case, which may be randomized.
+    let name = question.name.to_name();
+    if let Some(name_servers) = find_delegation(name_to_records, &name) {
+        // > If a match would take us out of the authoritative data, we have a referral.  This
+        // > happens when we encounter a node with NS RRs marking cuts along the bottom of a zone.
+        // > Copy the NS RRs for the subzone into the authority section of the reply.  Put whatever
+        // > addresses are available into the additional section, using glue RRs if the addresses
+        // > are not available from authoritative data or the cache.
+        // https://datatracker.ietf.org/doc/html/rfc1034#section-4.3.2
+        let mut response = request.answer_response(core::iter::empty())?;
+        response.header.authoritative_answer = false;
+        response.name_servers = name_servers.into_iter().cloned().collect();
+        add_additional_addresses(name_to_records, &mut response);
+        return Ok(response);
+    }
+    let records = name_to_records.get_vec(&name);
+    // Synthesized records make the name exist for queries of every type.
+    let synthesized = if config.synthesize_ptr
+        && !records
+            .into_iter()
+            .flatten()
+            .any(|record| record.typ() == DnsType::PTR)
+    {
+        synthesize_ptr(name_to_records, &name)
     } else {
-        request.answer_response(
-            records
-                .iter()
-                .filter(|record| record.typ() == question.typ)
-                .copied(),
-        )
+        Vec::new()
+    };
+    if records.is_none() && synthesized.is_empty() {
+        if !is_in_our_zones(zones, &name) {
+            return Err(DnsError::NotAuthoritative);
+        }
+        // A name with records below it exists even without records of its own, so it gets
+        // NODATA like any name without records of the requested type.
+        //
+        // > This document states clearly that when a DNS resolver receives a response with a
+        // > response code of NXDOMAIN, it means that the domain name which is thus denied AND ALL
+        // > THE NAMES UNDER IT do not exist.
+        // https://datatracker.ietf.org/doc/html/rfc8020
+        if !zones.empty_non_terminals.contains(&name) {
+            return Err(DnsError::NotFound);
+        }
+    }
+    let records = records.map(Vec::as_slice).unwrap_or_default();
+    let mut response = request.answer_response(
+        records
+            .iter()
+            .copied()
+            .chain(&synthesized)
+            .filter(|record| question.typ == DnsType::ANY || record.typ() == question.typ),
+    )?;
+    // > When a DNS server receives a query, it MUST, in the response, echo the question name
+    // > exactly as it was received.
+    // https://datatracker.ietf.org/doc/html/draft-vixie-dnsext-dns0x20-00#section-4
+    response.answers = response
+        .answers
+        .into_iter()
+        .map(|record| {
+            if record.name() == &name {
+                record.with_name(name.clone())
+            } else {
+                record
+            }
+        })
+        .collect();
+    if response.answers.is_empty() {
+        add_negative_soa(name_to_records, &mut response);
     }
+    add_additional_addresses(name_to_records, &mut response);
+    Ok(response)
 }
 
-/// # Errors
-/// Returns `Err` when the request is malformed or the server is not configured to answer the
-/// request.
-#[allow(clippy::implicit_hasher)]
-pub fn process_datagram(
+/// Makes PTR records for `name` that point to the owners of the A and AAAA records holding the
+/// address that `name` stands for.  See [`ServerConfig::synthesize_ptr`].
+fn synthesize_ptr(
+    name_to_records: &MultiMap<&DnsName, &DnsRecord>,
+    name: &DnsName,
+) -> Vec<DnsRecord> {
+    let Some(addr) = name.reverse_addr() else {
+        return Vec::new();
+    };
+    let mut records: Vec<DnsRecord> = name_to_records
+        .iter_all()
+        .flat_map(|(_, records)| records)
+        .filter(|record| match record.data() {
+            DnsRecordData::A(ipv4_addr) => IpAddr::V4(*ipv4_addr) == addr,
+            DnsRecordData::AAAA(ipv6_addr) => IpAddr::V6(*ipv6_addr) == addr,
+            _ => false,
+        })
+        .map(|record| {
+            DnsRecord::new(
+                name.clone(),
+                record.class(),
+                record.ttl_seconds(),
+                DnsRecordData::PTR(record.name().clone()),
+            )
+        })
+        .collect();
+    // The map has no order.  Sort so responses do not change between queries.
+    records.sort();
+    records.dedup();
+    records
+}
+
+/// Returns `true` when `name` is in a zone we serve.  See [`Zones::new`].
+fn is_in_our_zones(zones: &Zones<'_>, name: &DnsName) -> bool {
+    let mut name = Some(name.clone());
+    while let Some(ancestor) = name {
+        if zones.name_to_records.contains_key(&ancestor)
+            || zones.empty_non_terminals.contains(&ancestor)
+        {
+            return true;
+        }
+        name = ancestor.parent();
+    }
+    false
+}
+
+/// Returns the SOA record of the zone holding `name`, which is the SOA at `name` or at the
+/// closest name above it.
+fn find_soa<'r>(
+    name_to_records: &MultiMap<&DnsName, &'r DnsRecord>,
+    name: &DnsName,
+) -> Option<&'r DnsRecord> {
+    let mut name = Some(name.clone());
+    while let Some(ancestor) = name {
+        let soa = name_to_records
+            .get_vec(&ancestor)
+            .into_iter()
+            .flatten()
+            .find(|record| record.typ() == DnsType::SOA);
+        if let Some(soa) = soa {
+            return Some(soa);
+        }
+        name = ancestor.parent();
+    }
+    None
+}
+
+/// Adds the SOA record of the zone holding the question name to the authority section of a
+/// NXDOMAIN or NODATA response, so resolvers can cache the negative answer.
+///
+/// > Name servers authoritative for a zone MUST include the SOA record of the zone in the
+/// > authority section of the response when reporting an NXDOMAIN or indicating that no data of
+/// > the requested type exists.  This is required so that the response may be cached.  The TTL
+/// > of this record is set from the minimum of the MINIMUM field of the SOA record and the TTL
+/// > of the SOA itself, and indicates how long a resolver may cache the negative answer.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc2308#section-3>
+fn add_negative_soa(name_to_records: &MultiMap<&DnsName, &DnsRecord>, response: &mut DnsMessage) {
+    let Some(question) = response.questions.first() else {
+        return;
+    };
+    let Some(soa) = find_soa(name_to_records, &question.name) else {
+        return;
+    };
+    if let DnsRecordData::SOA { minimum, .. } = soa.data() {
+        let ttl_seconds = soa.ttl_seconds().min(*minimum);
+        response
+            .name_servers
+            .push(soa.clone().with_ttl(ttl_seconds));
+    }
+}
+
+/// Returns the NS records of the zone cut at or above `name`, when we delegated the zone holding
+/// `name` to other servers.
+///
+/// A name with NS records is a zone cut when we also hold records above it, in the parent zone.
+/// The NS records at our topmost name, and at names with an SOA record, are our own.
+fn find_delegation<'r>(
+    name_to_records: &MultiMap<&DnsName, &'r DnsRecord>,
+    name: &DnsName,
+) -> Option<Vec<&'r DnsRecord>> {
+    let mut ancestors = vec![name.clone()];
+    while let Some(parent) = ancestors.last().and_then(DnsName::parent) {
+        ancestors.push(parent);
+    }
+    let mut in_parent_zone = false;
+    for ancestor in ancestors.iter().rev() {
+        let Some(records) = name_to_records.get_vec(ancestor) else {
+            continue;
+        };
+        let name_servers: Vec<&DnsRecord> = records
+            .iter()
+            .copied()
+            .filter(|record| record.typ() == DnsType::NS)
+            .collect();
+        let is_zone_apex = records.iter().any(|record| record.typ() == DnsType::SOA);
+        if in_parent_zone && !is_zone_apex && !name_servers.is_empty() {
+            return Some(name_servers);
+        }
+        in_parent_zone = true;
+    }
+    None
+}
+
+/// Adds the A and AAAA records we hold for hosts named in the answer and authority sections to
+/// the additional section.
+///
+/// > MX records cause type A additional section processing for the host specified by EXCHANGE.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.9>
+///
+/// > NS records cause both the usual additional section processing to locate a type A record,
+/// > and, when used in a referral, a special search of the zone in which they reside for glue
+/// > information.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.11>
+///
+/// > Target
+/// >     The domain name of the target host.  There MUST be one or more address records for this
+/// >     name, the name MUST NOT be an alias (in the sense of RFC 1034 or RFC 2181).  Implementors
+/// >     are urged, but not required, to return the address record(s) in the Additional Data
+/// >     section.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc2782>
+fn add_additional_addresses(
     name_to_records: &MultiMap<&DnsName, &DnsRecord>,
-    bytes: &mut FixedBuf<512>,
-) -> Result<FixedBuf<512>, DnsError> {
+    response: &mut DnsMessage,
+) {
+    let hosts: Vec<DnsName> = response
+        .answers
+        .iter()
+        .chain(&response.name_servers)
+        .filter_map(|record| match record.data() {
+            DnsRecordData::MX { exchange: host, .. }
+            | DnsRecordData::NS(host)
+            | DnsRecordData::SRV { target: host, .. } => Some(host.clone()),
+            _ => None,
+        })
+        .collect();
+    for host in &hosts {
+        for record in name_to_records.get_vec(host).into_iter().flatten() {
+            if matches!(record.typ(), DnsType::A | DnsType::AAAA)
+                && !response.answers.contains(record)
+                && !response.additional.contains(record)
+            {
+                response.additional.push((*record).clone());
+            }
+        }
+    }
+}
+
+/// Makes the response to the request in `bytes`.  Malformed requests get a FORMERR response
+/// with only the header.
+///
+/// # Errors
+/// Returns `Err` when the server should not respond, like when `bytes` is shorter than a header,
+/// or when the response is larger than the client can receive.
+pub fn process_datagram<const N: usize>(
+    config: &ServerConfig,
+    zones: &Zones<'_>,
+    bytes: &mut FixedBuf<N>,
+) -> Result<FixedBuf<MAX_UDP_PAYLOAD_SIZE>, DnsError> {
     //println!("process_datagram: bytes = {:?}", bytes.readable());
-    let request = DnsMessage::read(bytes)?;
+    let request = match DnsMessageRef::new(bytes.readable()) {
+        Ok(request) => request,
+        Err(e) => {
+            let response = malformed_request_response(bytes.readable(), e)?;
+            return write_truncated(response, usize::from(MIN_UDP_PAYLOAD_SIZE));
+        }
+    };
     //println!("process_datagram: request = {:?}", request);
-    let response = process_request(name_to_records, &request)?;
+    let response = process_request(config, zones, &request)?;
     //println!("process_datagram: response = {:?}", response);
-    let mut out: FixedBuf<512> = FixedBuf::new();
-    response.write(&mut out)?;
+    let out = write_truncated(
+        response,
+        config.max_udp_response_len(request.edns().as_ref()),
+    )?;
     //println!("process_datagram: out = {:?}", out.readable());
     Ok(out)
 }
 
+/// Writes `response`, dropping RRsets until it fits in `max_len` bytes.
+///
+/// > Messages carried by UDP are restricted to 512 bytes (not counting the IP
+/// > or UDP headers).  Longer messages are truncated and the TC bit is set in
+/// > the header.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc1035#section-4.2.1>
+fn write_truncated(
+    mut response: DnsMessage,
+    max_len: usize,
+) -> Result<FixedBuf<MAX_UDP_PAYLOAD_SIZE>, DnsError> {
+    loop {
+        let mut out: FixedBuf<MAX_UDP_PAYLOAD_SIZE> = FixedBuf::new();
+        match response.write(&mut out) {
+            Ok(()) if out.len() <= max_len => return Ok(out),
+            Ok(()) | Err(DnsError::ResponseBufferFull) => {}
+            Err(e) => return Err(e),
+        }
+        if !response.drop_last_rrset() {
+            return Err(DnsError::ResponseBufferFull);
+        }
+    }
+}
+
 /// # Errors
 /// Returns `Err` when socket operations fail.
 #[allow(clippy::missing_panics_doc)]
@@ -63,6 +517,7 @@ pub fn serve_udp(
     permit: &permit::Permit,
     sock: &std::net::UdpSocket,
     mut response_bytes_rate_limiter: ProbRateLimiter,
+    config: &ServerConfig,
     records: &[DnsRecord],
 ) -> Result<(), String> {
     sock.set_read_timeout(Some(Duration::from_millis(500)))
@@ -70,14 +525,10 @@ pub fn serve_udp(
     let local_addr = sock
         .local_addr()
         .map_err(|e| format!("error getting socket local address: {e}"))?;
-    let name_to_records: MultiMap<&DnsName, &DnsRecord> =
-        records.iter().map(|x| (x.name(), x)).collect();
+    let zones = Zones::new(records);
     while !permit.is_revoked() {
-        // > Messages carried by UDP are restricted to 512 bytes (not counting the IP
-        // > or UDP headers).  Longer messages are truncated and the TC bit is set in
-        // > the header.
-        // https://datatracker.ietf.org/doc/html/rfc1035#section-4.2.1
-        let mut buf: FixedBuf<512> = FixedBuf::new();
+        // EDNS clients may send requests longer than 512 bytes.
+        let mut buf: FixedBuf<MAX_UDP_PAYLOAD_SIZE> = FixedBuf::new();
         let addr = match sock.recv_from(buf.writable()) {
             // Can this happen?  The docs are not clear.
             Ok((len, _)) if len > buf.writable().len() => continue,
@@ -95,7 +546,7 @@ pub fn serve_udp(
             println!("dropping request");
             continue;
         }
-        let out = match process_datagram(&name_to_records, &mut buf) {
+        let out = match process_datagram(config, &zones, &mut buf) {
             Ok(buf) => buf,
             Err(e) => {
                 println!("dropping bad request: {e:?}");
@@ -1,4 +1,4 @@
-use ddns::DnsRecord;
+use ddns::{DnsRecord, ServerConfig};
 use permit::Permit;
 use prob_rate_limiter::ProbRateLimiter;
 use std::net::{IpAddr, Ipv6Addr, SocketAddr, UdpSocket};
@@ -24,6 +24,7 @@ fn example() {
             &serve_udp_permit,
             &sock,
             response_bytes_rate_limiter,
+            &ServerConfig::default(),
             &records,
         )
         .unwrap();
@@ -90,6 +91,7 @@ fn hard_coded() {
             &serve_udp_permit,
             &server_sock,
             response_bytes_rate_limiter,
+            &ServerConfig::default(),
             &records,
         )
         .unwrap();
@@ -202,24 +204,9 @@ fn hard_coded() {
             0x00,
             0x01,
             // answer 0
-            // name=aaa.example.com
-            0x03,
-            97,
-            97,
-            97,
-            0x07,
-            101,
-            120,
-            97,
-            109,
-            112,
-            108,
-            101,
-            0x03,
-            99,
-            111,
-            109,
-            0x00,
+            // name=aaa.example.com (pointer to offset 12)
+            0xC0,
+            0x0C,
             // type=1 A
             0x00,
             0x01,
@@ -340,24 +327,9 @@ fn hard_coded() {
             0x00,
             0x01,
             // answer 0
-            // name=aaa.example.com
-            0x03,
-            97,
-            97,
-            97,
-            0x07,
-            101,
-            120,
-            97,
-            109,
-            112,
-            108,
-            101,
-            0x03,
-            99,
-            111,
-            109,
-            0x00,
+            // name=aaa.example.com (pointer to offset 12)
+            0xC0,
+            0x0C,
             // type=28 AAAA
             0x00,
             0x1C,
@@ -490,24 +462,9 @@ fn hard_coded() {
             0x00,
             0x01,
             // answer 0
-            // name=aaa.example.com
-            0x03,
-            97,
-            97,
-            97,
-            0x07,
-            101,
-            120,
-            97,
-            109,
-            112,
-            108,
-            101,
-            0x03,
-            99,
-            111,
-            109,
-            0x00,
+            // name=aaa.example.com (pointer to offset 12)
+            0xC0,
+            0x0C,
             // type=1 A
             0x00,
             0x01,
@@ -528,24 +485,9 @@ fn hard_coded() {
             0,
             1,
             // answer 1
-            // name=aaa.example.com
-            0x03,
-            97,
-            97,
-            97,
-            0x07,
-            101,
-            120,
-            97,
-            109,
-            112,
-            108,
-            101,
-            0x03,
-            99,
-            111,
-            109,
-            0x00,
+            // name=aaa.example.com (pointer to offset 12)
+            0xC0,
+            0x0C,
             // type=28 AAAA
             0x00,
             0x1C,
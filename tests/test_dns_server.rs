@@ -1,6 +1,8 @@
-use ddns::{process_datagram, DnsName, DnsRecord};
+use ddns::{
+    process_datagram, DnsClass, DnsEdns, DnsError, DnsMessage, DnsName, DnsQuestion, DnsRecord,
+    DnsResponseCode, DnsType, ServerConfig, Zones,
+};
 use fixed_buffer::FixedBuf;
-use multimap::MultiMap;
 
 #[test]
 fn test_process_datagram() {
@@ -16,12 +18,504 @@ fn test_process_datagram() {
     let expected_response = [
         0x9A, 0x9A, 0x85, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x03, 97, 97, 97,
         0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00, 0x01, 0x00, 0x01,
-        0x03, 97, 97, 97, 0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00,
-        0x01, 0x00, 0x01, 0x00, 0x00, 0x01, 0x2C, 0x00, 0x04, 10, 0, 0, 1_u8,
+        0xC0, 0x0C, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x01, 0x2C, 0x00, 0x04, 10, 0, 0, 1_u8,
     ];
     let records = [DnsRecord::new_a("aaa.example.com", "10.0.0.1").unwrap()];
-    let name_to_records: MultiMap<&DnsName, &DnsRecord> =
-        records.iter().map(|x| (x.name(), x)).collect();
-    let response = process_datagram(&name_to_records, &mut buf).unwrap();
+    let zones = Zones::new(&records);
+    let response = process_datagram(&ServerConfig::default(), &zones, &mut buf).unwrap();
     assert_eq!(expected_response, response.readable());
 }
+
+#[test]
+fn test_process_datagram_keeps_case() {
+    let records = [DnsRecord::new_a("aaa.example.com", "10.0.0.1").unwrap()];
+    let zones = Zones::new(&records);
+    // aAa.ExamPLE.cOm
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&[
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 97, 65, 97,
+        0x07, 69, 120, 97, 109, 80, 76, 69, 0x03, 99, 79, 109, 0x00, 0x00, 0x01, 0x00, 0x01,
+    ])
+    .unwrap();
+    let response = process_datagram(&ServerConfig::default(), &zones, &mut buf).unwrap();
+    let mut response_buf: FixedBuf<512> = FixedBuf::new();
+    response_buf.write_bytes(response.readable()).unwrap();
+    let response = DnsMessage::read(&mut response_buf).unwrap();
+    assert_eq!("aAa.ExamPLE.cOm", response.questions[0].name.inner());
+    assert_eq!(1, response.answers.len());
+    assert_eq!("aAa.ExamPLE.cOm", response.answers[0].name().inner());
+}
+
+#[test]
+fn test_process_datagram_edns() {
+    let records = [DnsRecord::new_a("aaa.example.com", "10.0.0.1").unwrap()];
+    let zones = Zones::new(&records);
+    let request = [
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x03, 97, 97, 97,
+        0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00, 0x01, 0x00, 0x01,
+        // OPT udp_payload_size=4096 DO=1
+        0x00, 0x00, 0x29, 0x10, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00,
+    ];
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&request).unwrap();
+    let config = ServerConfig {
+        max_udp_payload_size: 1400,
+        ..ServerConfig::default()
+    };
+    let response = process_datagram(&config, &zones, &mut buf).unwrap();
+    let mut response_buf: FixedBuf<512> = FixedBuf::new();
+    response_buf.write_bytes(response.readable()).unwrap();
+    let response = DnsMessage::read(&mut response_buf).unwrap();
+    assert_eq!(1, response.header.additional_count);
+    let mut expected_edns = DnsEdns::new(1400);
+    expected_edns.dnssec_ok = true;
+    assert_eq!(Some(expected_edns), response.edns);
+    assert_eq!(records.to_vec(), response.answers);
+}
+
+#[test]
+fn test_process_datagram_bad_edns_version() {
+    let records = [DnsRecord::new_a("aaa.example.com", "10.0.0.1").unwrap()];
+    let zones = Zones::new(&records);
+    let request = [
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x03, 97, 97, 97,
+        0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00, 0x01, 0x00, 0x01,
+        // OPT udp_payload_size=4096 version=1
+        0x00, 0x00, 0x29, 0x10, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+    ];
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&request).unwrap();
+    let response = process_datagram(&ServerConfig::default(), &zones, &mut buf).unwrap();
+    let mut response_buf: FixedBuf<512> = FixedBuf::new();
+    response_buf.write_bytes(response.readable()).unwrap();
+    let response = DnsMessage::read(&mut response_buf).unwrap();
+    assert_eq!(DnsResponseCode::NoError, response.header.response_code);
+    assert_eq!(DnsResponseCode::BadVersion, response.response_code());
+    assert!(response.answers.is_empty());
+    let edns = response.edns.unwrap();
+    assert_eq!(1, edns.extended_response_code);
+    assert_eq!(0, edns.version);
+    assert_eq!(1232, edns.udp_payload_size);
+}
+
+#[test]
+fn test_process_datagram_truncated() {
+    let records: Vec<DnsRecord> = (0..20)
+        .map(|n| DnsRecord::new_a("aaa.example.com", &format!("10.0.0.{n}")).unwrap())
+        .chain((0..10).map(|n| DnsRecord::new_aaaa("aaa.example.com", &format!("::{n}")).unwrap()))
+        .collect();
+    let zones = Zones::new(&records);
+    // type=ANY
+    let request = [
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 97, 97, 97,
+        0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00, 0xFF, 0x00, 0x01,
+    ];
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&request).unwrap();
+    let response = process_datagram(&ServerConfig::default(), &zones, &mut buf).unwrap();
+    assert!(response.len() <= 512);
+    let mut response_buf: FixedBuf<512> = FixedBuf::new();
+    response_buf.write_bytes(response.readable()).unwrap();
+    let response = DnsMessage::read(&mut response_buf).unwrap();
+    assert!(response.header.truncated);
+    // The whole AAAA RRset was dropped and the A RRset was kept.
+    assert_eq!(records[..20].to_vec(), response.answers);
+    // With EDNS the client gets everything.
+    let request = [
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x03, 97, 97, 97,
+        0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00, 0xFF, 0x00, 0x01,
+        // OPT udp_payload_size=4096
+        0x00, 0x00, 0x29, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(&request).unwrap();
+    let response = process_datagram(&ServerConfig::default(), &zones, &mut buf).unwrap();
+    let mut response_buf: FixedBuf<1232> = FixedBuf::new();
+    response_buf.write_bytes(response.readable()).unwrap();
+    let response = DnsMessage::read(&mut response_buf).unwrap();
+    assert!(!response.header.truncated);
+    assert_eq!(records, response.answers);
+}
+
+fn process(request: &[u8]) -> Result<DnsMessage, DnsError> {
+    let records = [DnsRecord::new_a("aaa.example.com", "10.0.0.1").unwrap()];
+    let zones = Zones::new(&records);
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    buf.write_bytes(request).unwrap();
+    let response = process_datagram(&ServerConfig::default(), &zones, &mut buf)?;
+    let mut response_buf: FixedBuf<512> = FixedBuf::new();
+    response_buf.write_bytes(response.readable()).unwrap();
+    DnsMessage::read(&mut response_buf)
+}
+
+#[test]
+fn test_process_datagram_name_error() {
+    // bbb.aaa.example.com
+    let response = process(&[
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 98, 98, 98,
+        0x03, 97, 97, 97, 0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00,
+        0x01, 0x00, 0x01,
+    ])
+    .unwrap();
+    assert_eq!(DnsResponseCode::NameError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert_eq!(1, response.questions.len());
+    assert!(response.answers.is_empty());
+}
+
+#[test]
+fn test_process_datagram_no_data() {
+    // type=AAAA
+    let response = process(&[
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 97, 97, 97,
+        0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00, 0x1C, 0x00, 0x01,
+    ])
+    .unwrap();
+    assert_eq!(DnsResponseCode::NoError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert!(response.answers.is_empty());
+}
+
+#[test]
+fn test_process_datagram_refused() {
+    // aaa.example.org
+    let response = process(&[
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 97, 97, 97,
+        0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 111, 114, 103, 0x00, 0x00, 0x01, 0x00, 0x01,
+    ])
+    .unwrap();
+    assert_eq!(DnsResponseCode::Refused, response.response_code());
+    assert!(!response.header.authoritative_answer);
+    assert_eq!(1, response.questions.len());
+}
+
+#[test]
+fn test_process_datagram_not_implemented() {
+    // opcode=STATUS
+    let response = process(&[
+        0x9A, 0x9A, 0x11, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 97, 97, 97,
+        0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00, 0x01, 0x00, 0x01,
+    ])
+    .unwrap();
+    assert_eq!(DnsResponseCode::NotImplemented, response.response_code());
+    assert_eq!(1, response.questions.len());
+}
+
+#[test]
+fn test_process_datagram_format_error() {
+    // The question is cut short.
+    let response = process(&[
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 97, 97,
+    ])
+    .unwrap();
+    assert_eq!(0x9A9A, response.header.id);
+    assert!(response.header.is_response);
+    assert_eq!(DnsResponseCode::FormatError, response.response_code());
+    assert!(response.questions.is_empty());
+    // No question.
+    let response = process(&[
+        0x9A, 0x9A, 1, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ])
+    .unwrap();
+    assert_eq!(DnsResponseCode::FormatError, response.response_code());
+    // Too short for a header.
+    assert_eq!(Err(DnsError::Truncated), process(&[0x9A, 0x9A, 1]));
+}
+
+#[test]
+fn test_process_datagram_drops_responses() {
+    assert_eq!(
+        Err(DnsError::NotARequest),
+        process(&[
+            0x9A, 0x9A, 0x81, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 97, 97,
+            97, 0x07, 101, 120, 97, 109, 112, 108, 101, 0x03, 99, 111, 109, 0x00, 0x00, 0x01, 0x00,
+            0x01,
+        ])
+    );
+    // Malformed responses too.
+    assert!(
+        process(&[0x9A, 0x9A, 0x81, 0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]).is_err()
+    );
+}
+
+/// Sends a query for `name` and `typ` to a server holding `records`.
+fn query(records: &[DnsRecord], name: &str, typ: DnsType) -> DnsMessage {
+    query_with_config(&ServerConfig::default(), records, name, typ)
+}
+
+fn query_with_config(
+    config: &ServerConfig,
+    records: &[DnsRecord],
+    name: &str,
+    typ: DnsType,
+) -> DnsMessage {
+    let zones = Zones::new(records);
+    let request = DnsMessage::query(
+        0x9A9A,
+        DnsQuestion {
+            name: DnsName::new(name).unwrap(),
+            typ,
+            class: DnsClass::Internet,
+        },
+    )
+    .build()
+    .unwrap();
+    let mut buf: FixedBuf<512> = FixedBuf::new();
+    request.write(&mut buf).unwrap();
+    let response = process_datagram(config, &zones, &mut buf).unwrap();
+    let mut response_buf: FixedBuf<512> = FixedBuf::new();
+    response_buf.write_bytes(response.readable()).unwrap();
+    DnsMessage::read(&mut response_buf).unwrap()
+}
+
+#[test]
+fn test_process_datagram_mx() {
+    let records = [
+        DnsRecord::new_mx("example.com", 10, "mail.example.com").unwrap(),
+        DnsRecord::new_mx("example.com", 20, "mail.example.org").unwrap(),
+        DnsRecord::new_a("mail.example.com", "10.0.0.1").unwrap(),
+        DnsRecord::new_aaaa("mail.example.com", "2001:db8::1").unwrap(),
+        DnsRecord::new_a("www.example.com", "10.0.0.2").unwrap(),
+    ];
+    let response = query(&records, "example.com", DnsType::MX);
+    assert_eq!(DnsResponseCode::NoError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert_eq!(records[..2].to_vec(), response.answers);
+    // We hold no addresses for mail.example.org.
+    assert_eq!(records[2..4].to_vec(), response.additional);
+    assert_eq!(2, response.header.additional_count);
+    // The exchange's addresses are not repeated when they are already answers.
+    let records = [
+        DnsRecord::new_mx("example.com", 10, "example.com").unwrap(),
+        DnsRecord::new_a("example.com", "10.0.0.1").unwrap(),
+    ];
+    let response = query(&records, "example.com", DnsType::ANY);
+    assert_eq!(records.to_vec(), response.answers);
+    assert!(response.additional.is_empty());
+}
+
+#[test]
+fn test_process_datagram_referral() {
+    let records = [
+        DnsRecord::new_ns("example.com", "ns1.example.com").unwrap(),
+        DnsRecord::new_a("ns1.example.com", "10.0.0.1").unwrap(),
+        DnsRecord::new_a("www.example.com", "10.0.0.2").unwrap(),
+        DnsRecord::new_ns("sub.example.com", "ns1.sub.example.com").unwrap(),
+        DnsRecord::new_ns("sub.example.com", "ns.example.org").unwrap(),
+        DnsRecord::new_a("ns1.sub.example.com", "10.0.0.3").unwrap(),
+    ];
+    for (name, typ) in [
+        ("www.sub.example.com", DnsType::A),
+        ("sub.example.com", DnsType::NS),
+        ("sub.example.com", DnsType::A),
+        // Glue is not authoritative data.
+        ("ns1.sub.example.com", DnsType::A),
+    ] {
+        let response = query(&records, name, typ);
+        assert_eq!(DnsResponseCode::NoError, response.response_code());
+        assert!(!response.header.authoritative_answer);
+        assert!(response.answers.is_empty());
+        assert_eq!(records[3..5].to_vec(), response.name_servers);
+        assert_eq!(records[5..].to_vec(), response.additional);
+    }
+    // NS records at the top are our own.
+    let response = query(&records, "example.com", DnsType::NS);
+    assert!(response.header.authoritative_answer);
+    assert_eq!(records[..1].to_vec(), response.answers);
+    assert!(response.name_servers.is_empty());
+    assert_eq!(records[1..2].to_vec(), response.additional);
+    let response = query(&records, "www.example.com", DnsType::A);
+    assert!(response.header.authoritative_answer);
+    assert_eq!(records[2..3].to_vec(), response.answers);
+}
+
+#[test]
+fn test_process_datagram_negative_soa() {
+    let soa = DnsRecord::new_soa(
+        "example.com",
+        "ns1.example.com",
+        "admin.example.com",
+        1,
+        7200,
+        3600,
+        1_209_600,
+        60,
+    )
+    .unwrap()
+    .with_ttl(3600);
+    let records = [
+        soa.clone(),
+        DnsRecord::new_ns("example.com", "ns1.example.com").unwrap(),
+        DnsRecord::new_a("www.example.com", "10.0.0.1").unwrap(),
+    ];
+    // NXDOMAIN
+    let response = query(&records, "nope.example.com", DnsType::A);
+    assert_eq!(DnsResponseCode::NameError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert!(response.answers.is_empty());
+    assert_eq!(vec![soa.clone().with_ttl(60)], response.name_servers);
+    // NODATA
+    let response = query(&records, "www.example.com", DnsType::AAAA);
+    assert_eq!(DnsResponseCode::NoError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert!(response.answers.is_empty());
+    assert_eq!(vec![soa.clone().with_ttl(60)], response.name_servers);
+    // The SOA's own TTL when it is lower than MINIMUM.
+    let records = [soa.clone().with_ttl(30)];
+    let response = query(&records, "www.example.com", DnsType::A);
+    assert_eq!(vec![soa.clone().with_ttl(30)], response.name_servers);
+    // Positive answers have no SOA.
+    let records = [
+        soa.clone(),
+        DnsRecord::new_a("www.example.com", "10.0.0.1").unwrap(),
+    ];
+    let response = query(&records, "www.example.com", DnsType::A);
+    assert_eq!(records[1..].to_vec(), response.answers);
+    assert!(response.name_servers.is_empty());
+    let response = query(&records, "example.com", DnsType::SOA);
+    assert_eq!(vec![soa], response.answers);
+    assert!(response.name_servers.is_empty());
+}
+
+#[test]
+fn test_process_datagram_child_zone_is_not_delegated() {
+    // We hold both the parent zone and the child zone.
+    let records = [
+        DnsRecord::new_ns("example.com", "ns1.example.com").unwrap(),
+        DnsRecord::new_soa(
+            "sub.example.com",
+            "ns1.example.com",
+            "admin.example.com",
+            1,
+            2,
+            3,
+            4,
+            5,
+        )
+        .unwrap(),
+        DnsRecord::new_ns("sub.example.com", "ns1.example.com").unwrap(),
+        DnsRecord::new_a("www.sub.example.com", "10.0.0.1").unwrap(),
+    ];
+    let response = query(&records, "www.sub.example.com", DnsType::A);
+    assert!(response.header.authoritative_answer);
+    assert_eq!(records[3..].to_vec(), response.answers);
+    let response = query(&records, "nope.sub.example.com", DnsType::A);
+    assert_eq!(DnsResponseCode::NameError, response.response_code());
+    assert_eq!(vec![records[1].clone().with_ttl(5)], response.name_servers);
+}
+
+#[test]
+fn test_process_datagram_synthesize_ptr() {
+    let config = ServerConfig {
+        synthesize_ptr: true,
+        ..ServerConfig::default()
+    };
+    let records = [
+        DnsRecord::new_a("www.example.com", "10.0.0.1")
+            .unwrap()
+            .with_ttl(60),
+        DnsRecord::new_a("web.example.com", "10.0.0.1").unwrap(),
+        DnsRecord::new_aaaa("www.example.com", "2001:db8::1").unwrap(),
+        DnsRecord::new_a("mail.example.com", "10.0.0.2").unwrap(),
+        DnsRecord::new_ptr("2.0.0.10.in-addr.arpa", "mx.example.com").unwrap(),
+    ];
+    let response = query_with_config(&config, &records, "1.0.0.10.in-addr.arpa", DnsType::PTR);
+    assert_eq!(DnsResponseCode::NoError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert_eq!(
+        vec![
+            DnsRecord::new_ptr("1.0.0.10.in-addr.arpa", "www.example.com")
+                .unwrap()
+                .with_ttl(60),
+            DnsRecord::new_ptr("1.0.0.10.in-addr.arpa", "web.example.com").unwrap(),
+        ],
+        response.answers
+    );
+    let name = "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa";
+    let response = query_with_config(&config, &records, name, DnsType::ANY);
+    assert_eq!(
+        vec![DnsRecord::new_ptr(name, "www.example.com").unwrap()],
+        response.answers
+    );
+    // Explicit PTR records win.
+    let response = query_with_config(&config, &records, "2.0.0.10.in-addr.arpa", DnsType::PTR);
+    assert_eq!(records[4..].to_vec(), response.answers);
+    // No address, no name.  The PTR record above puts it in our zone.
+    let response = query_with_config(&config, &records, "3.0.0.10.in-addr.arpa", DnsType::PTR);
+    assert_eq!(DnsResponseCode::NameError, response.response_code());
+    // The name exists, so other types get NODATA.
+    let response = query_with_config(&config, &records, "1.0.0.10.in-addr.arpa", DnsType::A);
+    assert_eq!(DnsResponseCode::NoError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert!(response.answers.is_empty());
+    // Off by default.
+    let response = query(&records, "1.0.0.10.in-addr.arpa", DnsType::PTR);
+    assert_eq!(DnsResponseCode::NameError, response.response_code());
+}
+
+#[test]
+fn test_process_datagram_srv() {
+    let records = [
+        DnsRecord::new_srv("_sip._tcp.example.com", 10, 60, 5060, "sip.example.com").unwrap(),
+        DnsRecord::new_srv("_sip._tcp.example.com", 20, 0, 5060, "backup.example.net").unwrap(),
+        DnsRecord::new_txt("_sip._tcp.example.com", "hi").unwrap(),
+        DnsRecord::new_a("sip.example.com", "10.0.0.1").unwrap(),
+        DnsRecord::new_aaaa("sip.example.com", "2001:db8::1").unwrap(),
+    ];
+    let response = query(&records, "_sip._tcp.example.com", DnsType::SRV);
+    assert_eq!(DnsResponseCode::NoError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert_eq!(records[..2].to_vec(), response.answers);
+    assert_eq!(records[3..].to_vec(), response.additional);
+    let response = query(&records, "_sip._tcp.example.com", DnsType::ANY);
+    assert_eq!(records[..3].to_vec(), response.answers);
+    assert_eq!(records[3..].to_vec(), response.additional);
+}
+
+#[test]
+fn test_process_datagram_zone_membership() {
+    let soa = DnsRecord::new_soa(
+        "example.com",
+        "ns1.example.com",
+        "admin.example.com",
+        1,
+        2,
+        3,
+        4,
+        60,
+    )
+    .unwrap();
+    let records = [
+        soa.clone(),
+        DnsRecord::new_a("www.example.com", "10.0.0.1").unwrap(),
+        DnsRecord::new_a("a.b.example.com", "10.0.0.2").unwrap(),
+    ];
+    // A sibling in the zone does not exist.
+    let response = query(&records, "mail.example.com", DnsType::A);
+    assert_eq!(DnsResponseCode::NameError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert_eq!(vec![soa.clone().with_ttl(60)], response.name_servers);
+    // An empty non-terminal exists, so it has no data rather than no name.
+    let response = query(&records, "b.example.com", DnsType::A);
+    assert_eq!(DnsResponseCode::NoError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert!(response.answers.is_empty());
+    assert_eq!(vec![soa.with_ttl(60)], response.name_servers);
+    // Names outside the zone are refused, even with records below them.
+    let response = query(&records, "com", DnsType::A);
+    assert_eq!(DnsResponseCode::Refused, response.response_code());
+    let response = query(&records, "example.org", DnsType::A);
+    assert_eq!(DnsResponseCode::Refused, response.response_code());
+    // Without an SOA, the zone starts at the parent of our topmost name.
+    let records = [DnsRecord::new_a("a.b.example.com", "10.0.0.2").unwrap()];
+    let response = query(&records, "b.example.com", DnsType::A);
+    assert_eq!(DnsResponseCode::NoError, response.response_code());
+    assert!(response.header.authoritative_answer);
+    assert!(response.answers.is_empty());
+    let response = query(&records, "c.b.example.com", DnsType::A);
+    assert_eq!(DnsResponseCode::NameError, response.response_code());
+    let response = query(&records, "c.a.b.example.com", DnsType::A);
+    assert_eq!(DnsResponseCode::NameError, response.response_code());
+    let response = query(&records, "example.com", DnsType::A);
+    assert_eq!(DnsResponseCode::Refused, response.response_code());
+}